@@ -1,5 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MuxFailure } from "./MuxFailure";
 import type { ProfileDto } from "./ProfileDto";
+import type { SourceAutoGroup } from "./SourceAutoGroup";
 import type { SubscribeSourceDto } from "./SubscribeSourceDto";
+import type { SubscribeSourceSyncResultDto } from "./SubscribeSourceSyncResultDto";
 
-export type ConfluenceDto = { id: number, template: string, creator: string, created_at: number, updated_at: number, mux_content: string, subscribe_sources: Array<SubscribeSourceDto>, profiles: Array<ProfileDto>, name: string, sub_upload?: number, sub_download?: number, sub_total?: number, sub_expire?: number, cron_expr?: string, cron_expr_tz?: string, cron_prev_at?: number, cron_err?: string, cron_next_at?: number, user_agent: string, };
+export type ConfluenceDto = { id: number, template: string, creator: string, created_at: number, updated_at: number, mux_content: string, subscribe_sources: Array<SubscribeSourceDto>, profiles: Array<ProfileDto>, name: string, sub_upload?: number, sub_download?: number, sub_total?: number, sub_expire?: number, cron_expr?: string, cron_expr_tz?: string, cron_prev_at?: number, cron_err?: string, cron_next_at?: number, user_agent: string, source_auto_group?: SourceAutoGroup, template_id?: number, degraded_mux: boolean, mux_failures?: Array<MuxFailure>, 
+/**
+ * only returned by a sync of the confluence
+ */
+sync_results?: Array<SubscribeSourceSyncResultDto>, };
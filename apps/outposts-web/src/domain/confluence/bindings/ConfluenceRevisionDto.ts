@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffStats } from "./DiffStats";
+import type { MuxDiff } from "./MuxDiff";
+import type { RevisionSource } from "./RevisionSource";
+
+export type ConfluenceRevisionDto = { id: number, confluence_id: number, 
+/**
+ * only returned when a single revision is requested
+ */
+content?: string, content_hash: string, sources: Array<RevisionSource>, diff_stats?: DiffStats, 
+/**
+ * only returned when a single revision is requested
+ */
+diff?: MuxDiff, created_at: number, };
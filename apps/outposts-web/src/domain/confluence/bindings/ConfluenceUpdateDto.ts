@@ -1,3 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceAutoGroup } from "./SourceAutoGroup";
 
-export type ConfluenceUpdateDto = { template?: string, user_agent?: string, name?: string, };
+export type ConfluenceUpdateDto = { template?: string, user_agent?: string, name?: string, 
+/**
+ * `null` disables the generated source groups
+ */
+source_auto_group?: SourceAutoGroup | null, 
+/**
+ * follow a library template, `null` goes back to the inline template
+ */
+template_id?: number | null, degraded_mux?: boolean, };
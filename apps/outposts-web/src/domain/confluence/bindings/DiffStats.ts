@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * changed lines of the mux content against the previous revision
+ */
+export type DiffStats = { added_lines: number, removed_lines: number, };
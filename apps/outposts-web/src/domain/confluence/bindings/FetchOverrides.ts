@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * per source replacements of the fetch defaults
+ */
+export type FetchOverrides = { connect_timeout_secs?: number, read_timeout_secs?: number, max_retries?: number, 
+/**
+ * bytes
+ */
+max_body_size?: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GroupChange } from "./GroupChange";
+import type { ProxyChange } from "./ProxyChange";
+
+/**
+ * what changed between two muxed configs
+ */
+export type MuxDiff = { added_proxies: Array<string>, removed_proxies: Array<string>, modified_proxies: Array<ProxyChange>, added_groups: Array<string>, removed_groups: Array<string>, changed_groups: Array<GroupChange>, added_rules: Array<string>, removed_rules: Array<string>, };
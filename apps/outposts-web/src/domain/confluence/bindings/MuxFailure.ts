@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a subscribe source left out of or muxed from an older snapshot in a degraded mux
+ */
+export type MuxFailure = { subscribe_source_id: number, name: string, reason: string, 
+/**
+ * the last valid snapshot muxed instead, none if the source was skipped
+ */
+fallback_snapshot_id?: number, };
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProfileTarget } from "./ProfileTarget";
 
-export type ProfileCreationDto = { confluence_id: number, };
+export type ProfileCreationDto = { confluence_id: number, target?: ProfileTarget, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProfileTarget } from "./ProfileTarget";
+
+export type ProfileUpdateDto = { target?: ProfileTarget, 
+/**
+ * pin the profile to a revision of its confluence, `null` follows the latest mux
+ */
+revision_id?: number | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a proxy in both configs with changed settings, matched by name or else by server and port
+ */
+export type ProxyChange = { name: string, 
+/**
+ * set if the proxy was matched by server and port under another name
+ */
+previous_name?: string, 
+/**
+ * changed settings other than the name
+ */
+fields: Array<string>, };
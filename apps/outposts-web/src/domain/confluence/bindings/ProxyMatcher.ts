@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * regexes matched against a proxy, a proxy matches if every given pattern matches,
+ * a matcher without any pattern is ignored
+ */
+export type ProxyMatcher = { name?: string, type?: string, server?: string, };
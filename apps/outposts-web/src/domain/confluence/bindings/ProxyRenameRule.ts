@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * replace every match of `pattern` in the proxy name, `replacement` may refer to groups like `$1`
+ */
+export type ProxyRenameRule = { pattern: string, replacement: string, };
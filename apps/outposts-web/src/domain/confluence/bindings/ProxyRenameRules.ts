@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProxyRenameRule } from "./ProxyRenameRule";
+
+/**
+ * rename rules applied in order
+ */
+export type ProxyRenameRules = Array<ProxyRenameRule>;
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProxyScheme } from "./ProxyScheme";
+
+/**
+ * an upstream proxy subscriptions are fetched through, stored as the `proxy_server` url
+ * and the `proxy_auth` basic credentials of a subscribe source, other `proxy_auth` schemes
+ * stored before are sent as they are
+ */
+export type ProxySettings = { scheme: ProxyScheme, host: string, port: number, username?: string, password?: string, };
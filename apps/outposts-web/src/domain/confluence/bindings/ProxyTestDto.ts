@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProxySettings } from "./ProxySettings";
+
+export type ProxyTestDto = { proxy: ProxySettings, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * extra headers sent when fetching, by name, the values are sealed at rest
+ */
+export type RequestHeaders = { [key in string]?: string };
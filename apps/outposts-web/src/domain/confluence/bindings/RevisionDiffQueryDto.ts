@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type RevisionDiffQueryDto = { 
+/**
+ * the revision to compare with, the previous revision by default
+ */
+against?: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * a subscribe source as it was muxed into a revision
+ */
+export type RevisionSource = { subscribe_source_id: number, name: string, 
+/**
+ * the fetched payload the source was muxed from, if it was kept
+ */
+snapshot_id?: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AutoGroupKind } from "./AutoGroupKind";
+
+/**
+ * an extra health checked group generated for every subscribe source when muxing
+ */
+export type SourceAutoGroup = { kind: AutoGroupKind, url?: string, 
+/**
+ * seconds between health checks
+ */
+interval?: number, 
+/**
+ * milliseconds, only used by url-test
+ */
+tolerance?: number, };
@@ -1,3 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FetchOverrides } from "./FetchOverrides";
+import type { ProxyMatcher } from "./ProxyMatcher";
+import type { ProxyRenameRules } from "./ProxyRenameRules";
+import type { ProxySettings } from "./ProxySettings";
+import type { RequestHeaders } from "./RequestHeaders";
 
-export type SubscribeSourceCreationDto = { confluence_id: number, url: string, name: string, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, };
+export type SubscribeSourceCreationDto = { confluence_id: number, url: string, name: string, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, 
+/**
+ * replaces `proxy_server` and `proxy_auth` if given
+ */
+proxy?: ProxySettings, import_proxy_groups: boolean | null, proxy_include: ProxyMatcher | null, proxy_exclude: ProxyMatcher | null, proxy_rename: ProxyRenameRules | null, tags: Array<string> | null, fetch_overrides: FetchOverrides | null, request_headers?: RequestHeaders, };
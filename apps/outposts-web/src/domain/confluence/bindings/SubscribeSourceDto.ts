@@ -1,3 +1,16 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FetchOverrides } from "./FetchOverrides";
+import type { ProxyMatcher } from "./ProxyMatcher";
+import type { ProxyRenameRules } from "./ProxyRenameRules";
+import type { ProxySettings } from "./ProxySettings";
+import type { RequestHeaders } from "./RequestHeaders";
 
-export type SubscribeSourceDto = { id: number, url: string, created_at: number, updated_at: number, confluence_id: number, name: string, content: string, sub_upload?: number, sub_download?: number, sub_total?: number, sub_expire?: number, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, };
+export type SubscribeSourceDto = { id: number, url: string, created_at: number, updated_at: number, confluence_id: number, name: string, content: string, sub_upload?: number, sub_download?: number, sub_total?: number, sub_expire?: number, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, 
+/**
+ * `proxy_server` and `proxy_auth` read back, if they can be
+ */
+proxy?: ProxySettings, import_proxy_groups: boolean | null, proxy_include: ProxyMatcher | null, proxy_exclude: ProxyMatcher | null, proxy_rename: ProxyRenameRules | null, tags: Array<string>, pinned_snapshot_id?: number, last_sync_at?: number, last_sync_status?: number, last_error?: string, consecutive_failures: number, fetch_overrides?: FetchOverrides, 
+/**
+ * values are masked unless revealed
+ */
+request_headers?: RequestHeaders, };
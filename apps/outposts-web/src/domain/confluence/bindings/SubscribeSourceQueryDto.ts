@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SubscribeSourceQueryDto = { 
+/**
+ * show the url and proxy credentials instead of masking them
+ */
+reveal?: boolean, };
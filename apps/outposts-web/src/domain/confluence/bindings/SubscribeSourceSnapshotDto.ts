@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SubscribeSourceSnapshotDto = { id: number, subscribe_source_id: number, 
+/**
+ * only returned when a single snapshot is requested
+ */
+content?: string, status: number, sub_upload?: number, sub_download?: number, sub_total?: number, sub_expire?: number, fetched_at: number, };
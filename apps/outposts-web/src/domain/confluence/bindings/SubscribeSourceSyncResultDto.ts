@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SubscribeSourceSyncStatus } from "./SubscribeSourceSyncStatus";
+
+export type SubscribeSourceSyncResultDto = { subscribe_source_id: number, name: string, status: SubscribeSourceSyncStatus, error?: string, };
@@ -1,3 +1,36 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FetchOverrides } from "./FetchOverrides";
+import type { ProxyMatcher } from "./ProxyMatcher";
+import type { ProxyRenameRules } from "./ProxyRenameRules";
+import type { ProxySettings } from "./ProxySettings";
+import type { RequestHeaders } from "./RequestHeaders";
 
-export type SubscribeSourceUpdateDto = { url: string | null, name: string | null, content: string | null, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, };
+export type SubscribeSourceUpdateDto = { url: string | null, name: string | null, content: string | null, passive_sync: boolean | null, proxy_server: string | null, proxy_auth: string | null, 
+/**
+ * replaces `proxy_server` and `proxy_auth` if given, `null` fetches without proxy
+ */
+proxy?: ProxySettings | null, import_proxy_groups: boolean | null, 
+/**
+ * `null` keeps every proxy
+ */
+proxy_include?: ProxyMatcher | null, 
+/**
+ * `null` drops no proxy
+ */
+proxy_exclude?: ProxyMatcher | null, 
+/**
+ * `null` keeps the proxy names of the source
+ */
+proxy_rename?: ProxyRenameRules | null, tags: Array<string> | null, 
+/**
+ * mux an earlier snapshot, `null` goes back to the latest content
+ */
+pinned_snapshot_id?: number | null, 
+/**
+ * `null` goes back to the server defaults
+ */
+fetch_overrides?: FetchOverrides | null, 
+/**
+ * replaces every header, masked values keep the stored value of the same header
+ */
+request_headers?: RequestHeaders, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TemplateDto = { id: number, name: string, creator: string, content: string, shared: boolean, version: number, created_at: number, updated_at: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TemplateUpdateDto = { name?: string, 
+/**
+ * a changed content creates a new version and re-muxes the following confluences
+ */
+content?: string, shared?: boolean, };
@@ -1,19 +1,24 @@
-#![feature(slice_take)]
-
 use axum::{
-    handler::HandlerWithoutStateExt, http::Method, http::StatusCode, middleware, routing::delete,
-    routing::get, routing::post, routing::put, Router,
+    Router, handler::HandlerWithoutStateExt, http::Method, http::StatusCode, middleware,
+    routing::get, routing::post, routing::put,
 };
 use confluence::auth::auth;
-use confluence::config::{AppConfig, AuthConfig};
+use confluence::config::{AppConfig, AuthConfig, FetchConfig};
+use confluence::crypto::SecretKey;
 use confluence::error::AppError;
 use confluence::migrations;
 use confluence::services::{
-    create_one_confluence, create_one_profile, create_one_subscribe_source, delete_one_confluence,
-    delete_one_profile, delete_one_subscribe_source, find_many_confluences, find_one_confluence,
-    find_one_profile_as_subscription_by_token, mux_one_confluence, sync_one_confluence,
-    sync_one_subscribe_source, update_one_confluence, update_one_confluence_cron,
-    update_one_subscribe_source, AppState,
+    AppState, create_one_confluence, create_one_profile, create_one_subscribe_source,
+    create_one_template, delete_one_confluence, delete_one_profile, delete_one_subscribe_source,
+    delete_one_template, find_many_confluence_revisions, find_many_confluences,
+    find_many_subscribe_source_snapshots, find_many_template_versions, find_many_templates,
+    find_one_confluence, find_one_confluence_revision, find_one_confluence_revision_diff,
+    find_one_profile_as_subscription_by_token, find_one_subscribe_source,
+    find_one_subscribe_source_snapshot, find_one_template, mux_one_confluence,
+    reseal_subscribe_source_secrets, rollback_one_confluence_revision, sync_one_confluence,
+    sync_one_subscribe_source, test_upstream_proxy, update_one_confluence,
+    update_one_confluence_cron, update_one_profile, update_one_subscribe_source,
+    update_one_template,
 };
 use confluence::tasks::init_backend_jobs;
 use sea_orm::{ConnectOptions, Database};
@@ -22,6 +27,7 @@ use std::env;
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_cron_scheduler::JobScheduler;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
@@ -59,6 +65,42 @@ async fn main() -> Result<(), AppError> {
     let host = env::var("CONFLUENCE_HOST").unwrap_or_else(|_| String::from("0.0.0.0"));
     let port = env::var("CONFLUENCE_PORT").map_or(4001u16, |p| p.parse::<u16>().unwrap());
 
+    let mut fetch = FetchConfig::default();
+    if let Ok(secs) = env::var("CONFLUENCE_FETCH_CONNECT_TIMEOUT_SECS") {
+        fetch.connect_timeout = Duration::from_secs(secs.parse::<u64>().unwrap());
+    }
+    if let Ok(secs) = env::var("CONFLUENCE_FETCH_READ_TIMEOUT_SECS") {
+        fetch.read_timeout = Duration::from_secs(secs.parse::<u64>().unwrap());
+    }
+    if let Ok(secs) = env::var("CONFLUENCE_FETCH_TOTAL_TIMEOUT_SECS") {
+        fetch.total_timeout = Duration::from_secs(secs.parse::<u64>().unwrap());
+    }
+    if let Ok(max_retries) = env::var("CONFLUENCE_FETCH_MAX_RETRIES") {
+        fetch.max_retries = max_retries.parse::<u32>().unwrap();
+    }
+    if let Ok(max_body_size) = env::var("CONFLUENCE_FETCH_MAX_BODY_SIZE") {
+        fetch.max_body_size = max_body_size.parse::<usize>().unwrap();
+    }
+    if let Ok(min_body_size) = env::var("CONFLUENCE_FETCH_MIN_BODY_SIZE") {
+        fetch.min_body_size = min_body_size.parse::<usize>().unwrap();
+    }
+    if let Ok(ca_bundle) = env::var("CONFLUENCE_FETCH_CA_BUNDLE") {
+        let pem = std::fs::read(&ca_bundle).map_err(anyhow::Error::from)?;
+        fetch.ca_certificates = reqwest::Certificate::from_pem_bundle(&pem)?;
+    }
+    if let Ok(accept_invalid_certs) = env::var("CONFLUENCE_FETCH_ACCEPT_INVALID_CERTS") {
+        fetch.accept_invalid_certs = accept_invalid_certs.parse::<bool>().unwrap();
+    }
+    if let Ok(allow_private_proxies) = env::var("CONFLUENCE_FETCH_ALLOW_PRIVATE_PROXIES") {
+        fetch.allow_private_proxies = allow_private_proxies.parse::<bool>().unwrap();
+    }
+
+    let secret_keys = env::var("CONFLUENCE_SECRET_KEYS")
+        .map_or_else(|_| Ok(vec![]), |keys| SecretKey::parse_many(&keys))?;
+    if secret_keys.is_empty() {
+        tracing::warn!("CONFLUENCE_SECRET_KEYS is not set, secrets are stored in plain text");
+    }
+
     {
         migrations::Migrator::up(&conn, None).await?;
     }
@@ -69,6 +111,8 @@ async fn main() -> Result<(), AppError> {
             port,
             host,
             database_url: db_url,
+            fetch,
+            secret_keys,
             auth: match &auth_type as &str {
                 "DEV_NO_AUTH" => {
                     let user_id =
@@ -94,6 +138,11 @@ async fn main() -> Result<(), AppError> {
         },
     ));
 
+    let resealed = reseal_subscribe_source_secrets(&state.conn, &state.cipher).await?;
+    if resealed > 0 {
+        tracing::info!("resealed secrets of {} subscribe sources", resealed);
+    }
+
     let mut job_scheduler = JobScheduler::new()
         .await
         .expect("failed to create backend task scheduler");
@@ -123,6 +172,19 @@ fn handle_confluence(state: Arc<AppState>) -> Router {
                 .delete(delete_one_confluence)
                 .put(update_one_confluence),
         )
+        .route("/{id}/revisions", get(find_many_confluence_revisions))
+        .route(
+            "/{id}/revisions/{revision_id}",
+            get(find_one_confluence_revision),
+        )
+        .route(
+            "/{id}/revisions/{revision_id}/diff",
+            get(find_one_confluence_revision_diff),
+        )
+        .route(
+            "/{id}/revisions/{revision_id}/rollback",
+            post(rollback_one_confluence_revision),
+        )
         .route("/mux/{id}", post(mux_one_confluence))
         .route("/sync/{id}", post(sync_one_confluence))
         .route("/cron/{id}", post(update_one_confluence_cron))
@@ -130,16 +192,35 @@ fn handle_confluence(state: Arc<AppState>) -> Router {
 
     let profile_api = Router::<Arc<AppState>>::new()
         .route("/", post(create_one_profile))
-        .route("/{id}", delete(delete_one_profile))
+        .route("/{id}", put(update_one_profile).delete(delete_one_profile))
         .layer(middleware::from_fn_with_state(state.clone(), auth));
 
     let subscribe_source_api = Router::<Arc<AppState>>::new()
         .route("/", post(create_one_subscribe_source))
         .route(
             "/{id}",
-            put(update_one_subscribe_source).delete(delete_one_subscribe_source),
+            get(find_one_subscribe_source)
+                .put(update_one_subscribe_source)
+                .delete(delete_one_subscribe_source),
+        )
+        .route("/{id}/snapshots", get(find_many_subscribe_source_snapshots))
+        .route(
+            "/{id}/snapshots/{snapshot_id}",
+            get(find_one_subscribe_source_snapshot),
         )
         .route("/sync/{id}", post(sync_one_subscribe_source))
+        .route("/test_proxy", post(test_upstream_proxy))
+        .layer(middleware::from_fn_with_state(state.clone(), auth));
+
+    let template_api = Router::<Arc<AppState>>::new()
+        .route("/", get(find_many_templates).post(create_one_template))
+        .route(
+            "/{id}",
+            get(find_one_template)
+                .put(update_one_template)
+                .delete(delete_one_template),
+        )
+        .route("/{id}/versions", get(find_many_template_versions))
         .layer(middleware::from_fn_with_state(state.clone(), auth));
 
     let profile_token_api = Router::<Arc<AppState>>::new()
@@ -151,6 +232,7 @@ fn handle_confluence(state: Arc<AppState>) -> Router {
         .nest("/api/profile", profile_api)
         .nest("/api/confluence", confluence_api)
         .nest("/api/subscribe_source", subscribe_source_api)
+        .nest("/api/template", template_api)
         .nest("/api/profile_token", profile_token_api)
         .nest("/api/health", health_api)
         .fallback_service(handle_404.into_service())
@@ -1,10 +1,14 @@
 pub mod http;
+pub mod rule;
+pub mod share_link;
 pub mod utils;
 
 pub use http::parse_subscription_userinfo_in_header;
 use monostate::MustBe;
+pub use rule::{IpCidr, Rule, RuleCondition};
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+pub use share_link::parse_subscription_content;
 use std::collections::HashMap;
 
 pub fn hysteria_v2_flatten_deserialize_with<'de, D>(
@@ -65,44 +69,421 @@ pub struct HysteriaV2Proxy {
     pub others: HashMap<String, Value>,
 }
 
+pub fn non_empty_string_deserialize_with<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    if value.trim().is_empty() {
+        return Err(serde::de::Error::custom("required field is empty"));
+    }
+    Ok(value)
+}
+
+/// providers serve ports both as integers and as strings
+pub fn port_deserialize_with<'de, D>(deserializer: D) -> Result<u16, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let port = match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_u64().and_then(|p| u16::try_from(p).ok()),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    port.ok_or_else(|| serde::de::Error::custom("invalid port"))
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn default_vmess_cipher() -> String {
+    String::from("auto")
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-pub struct OtherProxy {
+pub struct ShadowsocksProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("ss"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub cipher: String,
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShadowsocksRProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("ssr"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub cipher: String,
+    pub password: String,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub obfs: String,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub protocol: String,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VmessProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("vmess"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub uuid: String,
+    #[serde(rename = "alterId", default)]
+    pub alter_id: u32,
+    #[serde(default = "default_vmess_cipher")]
+    pub cipher: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tls: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servername: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VlessProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("vless"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub uuid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tls: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servername: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrojanProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("trojan"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sni: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+/// tuic v4 authenticates with `token`, while tuic v5 uses `uuid` and `password`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TuicProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("tuic"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+impl TuicProxy {
+    fn check_auth(self) -> Result<Self, String> {
+        let present = |v: &Option<String>| v.as_deref().is_some_and(|v| !v.trim().is_empty());
+        if present(&self.token) || (present(&self.uuid) && present(&self.password)) {
+            Ok(self)
+        } else {
+            Err(String::from("expect token, or uuid and password"))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WireguardProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("wireguard"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(
+        rename = "private-key",
+        deserialize_with = "non_empty_string_deserialize_with"
+    )]
+    pub private_key: String,
+    #[serde(
+        rename = "public-key",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub public_key: Option<String>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Socks5Proxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("socks5"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tls: bool,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HttpProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("http"),
     pub name: String,
     pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tls: bool,
     #[serde(flatten)]
     pub others: HashMap<String, Value>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SnellProxy {
+    #[serde(rename = "type")]
+    pub kind: MustBe!("snell"),
+    pub name: String,
+    pub server: String,
+    #[serde(deserialize_with = "port_deserialize_with")]
+    pub port: u16,
+    #[serde(deserialize_with = "non_empty_string_deserialize_with")]
+    pub psk: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OtherProxy {
+    pub name: String,
+    pub server: String,
+    #[serde(flatten)]
+    pub others: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum Proxy {
     HysteriaV1(HysteriaV1Proxy),
     HysteriaV2(HysteriaV2Proxy),
+    Shadowsocks(ShadowsocksProxy),
+    ShadowsocksR(ShadowsocksRProxy),
+    Vmess(VmessProxy),
+    Vless(VlessProxy),
+    Trojan(TrojanProxy),
+    Tuic(TuicProxy),
+    Wireguard(WireguardProxy),
+    Socks5(Socks5Proxy),
+    Http(HttpProxy),
+    Snell(SnellProxy),
     OtherProxy(OtherProxy),
 }
 
+/// dispatch on the `type` field, a proxy of a known type with missing or invalid
+/// required fields is kept as `OtherProxy` with a warning, instead of failing the whole config
+impl<'de> Deserialize<'de> for Proxy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let kind = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let res = match kind.as_str() {
+            "hysteria" => HysteriaV1Proxy::deserialize(value.clone()).map(Proxy::HysteriaV1),
+            "hysteria2" => HysteriaV2Proxy::deserialize(value.clone()).map(Proxy::HysteriaV2),
+            "ss" => ShadowsocksProxy::deserialize(value.clone()).map(Proxy::Shadowsocks),
+            "ssr" => ShadowsocksRProxy::deserialize(value.clone()).map(Proxy::ShadowsocksR),
+            "vmess" => VmessProxy::deserialize(value.clone()).map(Proxy::Vmess),
+            "vless" => VlessProxy::deserialize(value.clone()).map(Proxy::Vless),
+            "trojan" => TrojanProxy::deserialize(value.clone()).map(Proxy::Trojan),
+            "tuic" => TuicProxy::deserialize(value.clone())
+                .and_then(|p| p.check_auth().map_err(serde::de::Error::custom))
+                .map(Proxy::Tuic),
+            "wireguard" => WireguardProxy::deserialize(value.clone()).map(Proxy::Wireguard),
+            "socks5" => Socks5Proxy::deserialize(value.clone()).map(Proxy::Socks5),
+            "http" => HttpProxy::deserialize(value.clone()).map(Proxy::Http),
+            "snell" => SnellProxy::deserialize(value.clone()).map(Proxy::Snell),
+            _ => OtherProxy::deserialize(value.clone()).map(Proxy::OtherProxy),
+        };
+        let name = value
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        match res {
+            Ok(proxy) => Ok(proxy),
+            Err(e) => match OtherProxy::deserialize(value.clone()) {
+                Ok(proxy) => {
+                    tracing::warn!("keep invalid {} proxy {} untyped: {}", kind, name, e);
+                    Ok(Proxy::OtherProxy(proxy))
+                }
+                Err(_) => Err(serde::de::Error::custom(format!(
+                    "invalid {} proxy {}: {}",
+                    kind, name, e
+                ))),
+            },
+        }
+    }
+}
+
+macro_rules! match_proxy {
+    ($proxy:expr, $p:ident => $body:expr) => {
+        match $proxy {
+            Proxy::HysteriaV1($p) => $body,
+            Proxy::HysteriaV2($p) => $body,
+            Proxy::Shadowsocks($p) => $body,
+            Proxy::ShadowsocksR($p) => $body,
+            Proxy::Vmess($p) => $body,
+            Proxy::Vless($p) => $body,
+            Proxy::Trojan($p) => $body,
+            Proxy::Tuic($p) => $body,
+            Proxy::Wireguard($p) => $body,
+            Proxy::Socks5($p) => $body,
+            Proxy::Http($p) => $body,
+            Proxy::Snell($p) => $body,
+            Proxy::OtherProxy($p) => $body,
+        }
+    };
+}
+
 impl Proxy {
     pub fn name(&self) -> &str {
+        match_proxy!(self, proxy => &proxy.name)
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        match_proxy!(self, proxy => proxy.name = name)
+    }
+
+    pub fn server(&self) -> &str {
+        match_proxy!(self, proxy => &proxy.server)
+    }
+
+    pub fn others(&self) -> &HashMap<String, Value> {
+        match_proxy!(self, proxy => &proxy.others)
+    }
+
+    /// the clash `type` of the proxy
+    pub fn kind(&self) -> &str {
         match self {
-            Proxy::HysteriaV2(proxy) => &proxy.name,
-            Proxy::HysteriaV1(proxy) => &proxy.name,
-            Proxy::OtherProxy(proxy) => &proxy.name,
+            Proxy::HysteriaV1(_) => "hysteria",
+            Proxy::HysteriaV2(_) => "hysteria2",
+            Proxy::Shadowsocks(_) => "ss",
+            Proxy::ShadowsocksR(_) => "ssr",
+            Proxy::Vmess(_) => "vmess",
+            Proxy::Vless(_) => "vless",
+            Proxy::Trojan(_) => "trojan",
+            Proxy::Tuic(_) => "tuic",
+            Proxy::Wireguard(_) => "wireguard",
+            Proxy::Socks5(_) => "socks5",
+            Proxy::Http(_) => "http",
+            Proxy::Snell(_) => "snell",
+            Proxy::OtherProxy(proxy) => proxy
+                .others
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or_default(),
         }
     }
 
-    pub fn set_name(&mut self, name: String) {
+    pub fn port(&self) -> Option<u16> {
         match self {
-            Proxy::HysteriaV2(proxy) => proxy.name = name,
-            Proxy::HysteriaV1(proxy) => proxy.name = name,
-            Proxy::OtherProxy(proxy) => proxy.name = name,
+            Proxy::Shadowsocks(proxy) => Some(proxy.port),
+            Proxy::ShadowsocksR(proxy) => Some(proxy.port),
+            Proxy::Vmess(proxy) => Some(proxy.port),
+            Proxy::Vless(proxy) => Some(proxy.port),
+            Proxy::Trojan(proxy) => Some(proxy.port),
+            Proxy::Tuic(proxy) => Some(proxy.port),
+            Proxy::Wireguard(proxy) => Some(proxy.port),
+            Proxy::Socks5(proxy) => Some(proxy.port),
+            Proxy::Http(proxy) => Some(proxy.port),
+            Proxy::Snell(proxy) => Some(proxy.port),
+            Proxy::HysteriaV1(_) | Proxy::HysteriaV2(_) | Proxy::OtherProxy(_) => self
+                .others()
+                .get("port")
+                .and_then(|p| match p {
+                    Value::Number(n) => n.as_u64(),
+                    Value::String(s) => s.parse().ok(),
+                    _ => None,
+                })
+                .and_then(|p| u16::try_from(p).ok()),
         }
     }
 
-    pub fn server(&self) -> &str {
+    /// whether the proxy connection is wrapped in tls,
+    /// trojan, hysteria and tuic always are
+    pub fn tls(&self) -> bool {
         match self {
-            Proxy::HysteriaV2(proxy) => &proxy.server,
-            Proxy::HysteriaV1(proxy) => &proxy.server,
-            Proxy::OtherProxy(proxy) => &proxy.server,
+            Proxy::Vmess(proxy) => proxy.tls,
+            Proxy::Vless(proxy) => proxy.tls,
+            Proxy::Socks5(proxy) => proxy.tls,
+            Proxy::Http(proxy) => proxy.tls,
+            Proxy::Trojan(_) | Proxy::HysteriaV1(_) | Proxy::HysteriaV2(_) | Proxy::Tuic(_) => true,
+            Proxy::Shadowsocks(_)
+            | Proxy::ShadowsocksR(_)
+            | Proxy::Wireguard(_)
+            | Proxy::Snell(_) => false,
+            Proxy::OtherProxy(proxy) => proxy
+                .others
+                .get("tls")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
         }
     }
 }
@@ -120,10 +501,27 @@ impl Proxy {
 #[serde(rename_all = "snake_case")]
 pub enum ProxyGroupKind {
     Select,
+    #[serde(rename = "url-test")]
+    UrlTest,
+    Fallback,
+    #[serde(rename = "load-balance")]
+    LoadBalance,
     #[serde(other)]
     Other(String),
 }
 
+impl ProxyGroupKind {
+    pub fn name(&self) -> &str {
+        match self {
+            ProxyGroupKind::Select => "select",
+            ProxyGroupKind::UrlTest => "url-test",
+            ProxyGroupKind::Fallback => "fallback",
+            ProxyGroupKind::LoadBalance => "load-balance",
+            ProxyGroupKind::Other(kind) => kind,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ProxyGroup {
     pub name: String,
@@ -134,10 +532,7 @@ pub struct ProxyGroup {
     pub proxies: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
-pub struct Rule(pub String);
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct ClashConfig {
     #[serde(flatten)]
     pub others: HashMap<String, Value>,
@@ -149,8 +544,8 @@ pub struct ClashConfig {
 
 #[cfg(test)]
 mod tests {
-    use super::{ClashConfig, Proxy};
-    use std::assert_matches::assert_matches;
+    use super::{ClashConfig, Proxy, ProxyGroupKind};
+    use std::assert_matches;
 
     #[test]
     fn test_model() -> anyhow::Result<()> {
@@ -220,4 +615,112 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_typed_proxies() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+
+        assert_matches!(&config.proxies[0], &Proxy::Shadowsocks(_));
+        assert_eq!(config.proxies[0].kind(), "ss");
+        assert_eq!(config.proxies[0].port(), Some(22008));
+        assert!(!config.proxies[0].tls());
+
+        let vmess: Proxy = serde_yaml::from_str(
+            r#"{ name: v, type: vmess, server: v.example.com, port: 443, uuid: "b831381d-6324-4d53-ad4f-8cda48b30811", tls: true, network: ws, ws-opts: { path: /ray } }"#,
+        )?;
+        assert_matches!(&vmess, &Proxy::Vmess(_));
+        assert!(vmess.tls());
+
+        if let Proxy::Vmess(proxy) = &vmess {
+            assert_eq!(proxy.cipher, "auto");
+            assert_eq!(proxy.alter_id, 0);
+            assert_eq!(proxy.network.as_deref(), Some("ws"));
+            assert!(proxy.others.contains_key("ws-opts"));
+        }
+
+        let serialized = serde_yaml::to_value(&vmess)?;
+        assert_eq!(
+            serialized.get("type").and_then(|v| v.as_str()),
+            Some("vmess")
+        );
+        assert_eq!(serialized.get("port").and_then(|v| v.as_u64()), Some(443));
+        assert_eq!(
+            serde_yaml::from_value::<Proxy>(serialized)?,
+            vmess,
+            "typed proxy should round trip"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_proxy_validation() -> anyhow::Result<()> {
+        let missing_uuid = r#"{ name: v, type: vless, server: v.example.com, port: 443 }"#;
+        let missing_uuid: Proxy = serde_yaml::from_str(missing_uuid)?;
+        assert_matches!(&missing_uuid, &Proxy::OtherProxy(_));
+        assert_eq!(missing_uuid.kind(), "vless");
+
+        let empty_cipher =
+            r#"{ name: s, type: ss, server: s.example.com, port: 443, cipher: "", password: p }"#;
+        assert_matches!(
+            serde_yaml::from_str::<Proxy>(empty_cipher)?,
+            Proxy::OtherProxy(_)
+        );
+
+        let invalid_port =
+            r#"{ name: t, type: trojan, server: t.example.com, port: 70000, password: p }"#;
+        let invalid_port: Proxy = serde_yaml::from_str(invalid_port)?;
+        assert_matches!(&invalid_port, &Proxy::OtherProxy(_));
+        assert_eq!(invalid_port.port(), None);
+
+        let string_port =
+            r#"{ name: t, type: trojan, server: t.example.com, port: "443", password: p }"#;
+        let string_port: Proxy = serde_yaml::from_str(string_port)?;
+        assert_matches!(&string_port, &Proxy::Trojan(_));
+        assert_eq!(string_port.port(), Some(443));
+
+        let tuic_v4 = r#"{ name: t, type: tuic, server: t.example.com, port: 443, token: t }"#;
+        assert_matches!(serde_yaml::from_str::<Proxy>(tuic_v4)?, Proxy::Tuic(_));
+        let tuic_v5 =
+            r#"{ name: t, type: tuic, server: t.example.com, port: 443, uuid: u, password: p }"#;
+        assert_matches!(serde_yaml::from_str::<Proxy>(tuic_v5)?, Proxy::Tuic(_));
+        let tuic_no_auth = r#"{ name: t, type: tuic, server: t.example.com, port: 443, uuid: u }"#;
+        assert_matches!(
+            serde_yaml::from_str::<Proxy>(tuic_no_auth)?,
+            Proxy::OtherProxy(_)
+        );
+
+        let no_server = r#"{ name: v, type: vless, port: 443 }"#;
+        assert!(serde_yaml::from_str::<Proxy>(no_server).is_err());
+
+        let unknown = r#"{ name: m, type: mieru, server: m.example.com, port: 443 }"#;
+        let unknown: Proxy = serde_yaml::from_str(unknown).expect("should parse unknown proxy");
+        assert_matches!(&unknown, &Proxy::OtherProxy(_));
+        assert_eq!(unknown.kind(), "mieru");
+        assert_eq!(unknown.port(), Some(443));
+        Ok(())
+    }
+
+    #[test]
+    fn test_proxy_group_kind() -> anyhow::Result<()> {
+        let kinds: Vec<ProxyGroupKind> =
+            serde_yaml::from_str("[select, url-test, fallback, load-balance, relay]")?;
+
+        assert_eq!(
+            kinds,
+            vec![
+                ProxyGroupKind::Select,
+                ProxyGroupKind::UrlTest,
+                ProxyGroupKind::Fallback,
+                ProxyGroupKind::LoadBalance,
+                ProxyGroupKind::Other("relay".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_yaml::to_string(&kinds)?,
+            "- select\n- url-test\n- fallback\n- load-balance\n- relay\n"
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,403 @@
+use crate::error::ConfigError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+const NO_RESOLVE: &str = "no-resolve";
+
+/// an ip network in cidr notation such as `10.0.0.0/8`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct IpCidr {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpCidr {
+    /// the network containing only the given address
+    pub fn host(addr: IpAddr) -> Self {
+        let prefix = if addr.is_ipv6() { 128 } else { 32 };
+        Self { addr, prefix }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = s
+            .split_once('/')
+            .ok_or_else(|| format!("cidr {} is missing the prefix length", s))?;
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|e| format!("cidr {} has invalid address: {}", s, e))?;
+        let prefix = prefix
+            .parse::<u8>()
+            .map_err(|e| format!("cidr {} has invalid prefix length: {}", s, e))?;
+        let max_prefix = if addr.is_ipv6() { 128 } else { 32 };
+        if prefix > max_prefix {
+            return Err(format!("cidr {} prefix length exceeds {}", s, max_prefix));
+        }
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl Display for IpCidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// the matching part of a clash rule, everything except the target policy and options
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub enum RuleCondition {
+    Domain(String),
+    DomainSuffix(String),
+    DomainKeyword(String),
+    IpCidr(IpCidr),
+    IpCidr6(IpCidr),
+    GeoIp(String),
+    GeoSite(String),
+    ProcessName(String),
+    RuleSet(String),
+    And(Vec<RuleCondition>),
+    Or(Vec<RuleCondition>),
+    Not(Box<RuleCondition>),
+    /// `MATCH`, only valid as a top-level rule
+    Match,
+    /// rule types not modeled above, kept verbatim
+    Other {
+        kind: String,
+        payload: String,
+    },
+    /// a whole rule which failed to parse, kept verbatim so it round trips
+    Opaque(String),
+}
+
+impl RuleCondition {
+    /// the rule type keyword, such as `DOMAIN-SUFFIX`
+    pub fn kind(&self) -> &str {
+        match self {
+            RuleCondition::Domain(_) => "DOMAIN",
+            RuleCondition::DomainSuffix(_) => "DOMAIN-SUFFIX",
+            RuleCondition::DomainKeyword(_) => "DOMAIN-KEYWORD",
+            RuleCondition::IpCidr(_) => "IP-CIDR",
+            RuleCondition::IpCidr6(_) => "IP-CIDR6",
+            RuleCondition::GeoIp(_) => "GEOIP",
+            RuleCondition::GeoSite(_) => "GEOSITE",
+            RuleCondition::ProcessName(_) => "PROCESS-NAME",
+            RuleCondition::RuleSet(_) => "RULE-SET",
+            RuleCondition::And(_) => "AND",
+            RuleCondition::Or(_) => "OR",
+            RuleCondition::Not(_) => "NOT",
+            RuleCondition::Match => "MATCH",
+            RuleCondition::Other { kind, .. } => kind,
+            RuleCondition::Opaque(raw) => raw.split(',').next().unwrap_or_default().trim(),
+        }
+    }
+
+    /// the rule payload in clash syntax, empty for `MATCH`
+    pub fn payload(&self) -> String {
+        match self {
+            RuleCondition::Domain(v)
+            | RuleCondition::DomainSuffix(v)
+            | RuleCondition::DomainKeyword(v)
+            | RuleCondition::GeoIp(v)
+            | RuleCondition::GeoSite(v)
+            | RuleCondition::ProcessName(v)
+            | RuleCondition::RuleSet(v) => v.clone(),
+            RuleCondition::IpCidr(cidr) | RuleCondition::IpCidr6(cidr) => cidr.to_string(),
+            RuleCondition::And(conditions) | RuleCondition::Or(conditions) => format!(
+                "({})",
+                conditions
+                    .iter()
+                    .map(|c| format!("({})", c))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            RuleCondition::Not(condition) => format!("(({}))", condition),
+            RuleCondition::Match => String::new(),
+            RuleCondition::Other { payload, .. } => payload.clone(),
+            RuleCondition::Opaque(_) => String::new(),
+        }
+    }
+
+    fn parse(kind: &str, payload: &str) -> Result<Self, String> {
+        if payload.is_empty() {
+            return Err(format!("{} rule has empty payload", kind));
+        }
+        let value = payload.to_string();
+        let condition = match kind {
+            "DOMAIN" => RuleCondition::Domain(value),
+            "DOMAIN-SUFFIX" => RuleCondition::DomainSuffix(value),
+            "DOMAIN-KEYWORD" => RuleCondition::DomainKeyword(value),
+            "IP-CIDR" => RuleCondition::IpCidr(payload.parse()?),
+            "IP-CIDR6" => RuleCondition::IpCidr6(payload.parse()?),
+            "GEOIP" => RuleCondition::GeoIp(value),
+            "GEOSITE" => RuleCondition::GeoSite(value),
+            "PROCESS-NAME" => RuleCondition::ProcessName(value),
+            "RULE-SET" => RuleCondition::RuleSet(value),
+            "AND" | "OR" | "NOT" => {
+                let inner = payload
+                    .strip_prefix('(')
+                    .and_then(|p| p.strip_suffix(')'))
+                    .ok_or_else(|| format!("{} rule payload must be parenthesized", kind))?;
+                let conditions = split_top_level(inner)?
+                    .into_iter()
+                    .map(|c| {
+                        let c = c
+                            .strip_prefix('(')
+                            .and_then(|c| c.strip_suffix(')'))
+                            .ok_or_else(|| format!("sub rule {} must be parenthesized", c))?;
+                        let (kind, payload) = c.split_once(',').unwrap_or((c, ""));
+                        match kind.trim() {
+                            "MATCH" => Err(String::from("MATCH can not be a sub rule")),
+                            kind => RuleCondition::parse(kind, payload.trim()),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                match kind {
+                    "AND" => RuleCondition::And(conditions),
+                    "OR" => RuleCondition::Or(conditions),
+                    _ => {
+                        let [condition]: [RuleCondition; 1] = conditions
+                            .try_into()
+                            .map_err(|_| String::from("NOT rule takes exactly one sub rule"))?;
+                        RuleCondition::Not(Box::new(condition))
+                    }
+                }
+            }
+            kind => RuleCondition::Other {
+                kind: kind.to_string(),
+                payload: value,
+            },
+        };
+        Ok(condition)
+    }
+}
+
+impl Display for RuleCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleCondition::Match => f.write_str("MATCH"),
+            RuleCondition::Opaque(raw) => f.write_str(raw),
+            condition => write!(f, "{},{}", condition.kind(), condition.payload()),
+        }
+    }
+}
+
+/// split on commas which are not nested in parentheses
+fn split_top_level(s: &str) -> Result<Vec<&str>, String> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("unbalanced parentheses in {}", s))?;
+            }
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("unbalanced parentheses in {}", s));
+    }
+    parts.push(s[start..].trim());
+    Ok(parts)
+}
+
+/// a clash rule `TYPE,payload,target[,no-resolve]`, serialized as its string form,
+/// a rule which fails to deserialize is kept as `RuleCondition::Opaque`
+#[derive(Clone, Debug, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Rule {
+    pub condition: RuleCondition,
+    pub target: String,
+    pub no_resolve: bool,
+}
+
+impl Rule {
+    pub fn new(condition: RuleCondition, target: impl Into<String>) -> Self {
+        Self {
+            condition,
+            target: target.into(),
+            no_resolve: false,
+        }
+    }
+
+    /// keep a rule which failed to parse as is
+    pub fn opaque(raw: impl Into<String>) -> Self {
+        Self::new(RuleCondition::Opaque(raw.into()), String::new())
+    }
+
+    /// error out on an opaque rule, for places interpreting rules
+    pub fn check(&self) -> Result<(), ConfigError> {
+        match &self.condition {
+            RuleCondition::Opaque(raw) => raw.parse::<Rule>().map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| ConfigError::RuleInvalid {
+            rule: s.to_string(),
+            reason,
+        };
+        let mut parts = split_top_level(s.trim()).map_err(invalid)?;
+        let no_resolve = parts.len() > 2 && parts.last() == Some(&NO_RESOLVE);
+        if no_resolve {
+            parts.pop();
+        }
+        let (condition, target) = match parts.as_slice() {
+            ["MATCH", target] => (RuleCondition::Match, *target),
+            [kind, payload, target] if *kind != "MATCH" => (
+                RuleCondition::parse(kind, payload).map_err(invalid)?,
+                *target,
+            ),
+            _ => {
+                return Err(invalid(String::from(
+                    "expected TYPE,payload,target[,no-resolve]",
+                )));
+            }
+        };
+        if target.is_empty() {
+            return Err(invalid(String::from("rule has empty target")));
+        }
+        Ok(Self {
+            condition,
+            target: target.to_string(),
+            no_resolve,
+        })
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let RuleCondition::Opaque(raw) = &self.condition {
+            return f.write_str(raw);
+        }
+        write!(f, "{},{}", self.condition, self.target)?;
+        if self.no_resolve {
+            write!(f, ",{}", NO_RESOLVE)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|_| Rule::opaque(s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches;
+
+    #[test]
+    fn test_rule_round_trip() -> anyhow::Result<()> {
+        for s in [
+            "DOMAIN,example.com,Proxy",
+            "DOMAIN-SUFFIX,google.com,Proxy",
+            "DOMAIN-KEYWORD,ads,REJECT",
+            "IP-CIDR,10.0.0.0/8,DIRECT,no-resolve",
+            "IP-CIDR6,2001:db8::/32,DIRECT",
+            "GEOIP,CN,DIRECT",
+            "GEOSITE,cn,DIRECT",
+            "PROCESS-NAME,curl,DIRECT",
+            "RULE-SET,reject,REJECT",
+            "AND,((DOMAIN,example.com),(DST-PORT,443)),Proxy",
+            "OR,((GEOIP,CN),(NOT,((DOMAIN-KEYWORD,google)))),DIRECT",
+            "DST-PORT,22,DIRECT",
+            "MATCH,Proxy",
+        ] {
+            let rule: Rule = s.parse()?;
+            assert_eq!(rule.to_string(), s);
+        }
+
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            r"
+- IP-CIDR, 192.168.0.0/16 , DIRECT , no-resolve
+- NOT,((DOMAIN,example.com)),REJECT
+            ",
+        )?;
+        assert_eq!(
+            rules[0],
+            Rule {
+                condition: RuleCondition::IpCidr("192.168.0.0/16".parse().unwrap()),
+                target: "DIRECT".to_string(),
+                no_resolve: true,
+            }
+        );
+        assert_eq!(
+            rules[1].condition,
+            RuleCondition::Not(Box::new(RuleCondition::Domain("example.com".to_string())))
+        );
+        assert_eq!(
+            serde_yaml::to_string(&rules)?,
+            "- IP-CIDR,192.168.0.0/16,DIRECT,no-resolve\n- NOT,((DOMAIN,example.com)),REJECT\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rule_invalid() {
+        for s in [
+            "DOMAIN,example.com",
+            "DOMAIN,,Proxy",
+            "IP-CIDR,10.0.0.0,DIRECT",
+            "IP-CIDR,10.0.0.0/33,DIRECT",
+            "AND,(DOMAIN,example.com),Proxy",
+            "AND,((DOMAIN,example.com),(MATCH,x)),Proxy",
+            "NOT,((DOMAIN,a),(DOMAIN,b)),Proxy",
+            "OR,((DOMAIN,a),Proxy",
+            "MATCH,a,b",
+            "MATCH,",
+        ] {
+            assert_matches!(
+                s.parse::<Rule>(),
+                Err(ConfigError::RuleInvalid { .. }),
+                "{}",
+                s
+            );
+        }
+
+        let rules: Vec<Rule> = serde_yaml::from_str("- DOMAIN,example.com\n- SCRIPT,quic,REJECT")
+            .expect("should keep invalid rules");
+        assert_eq!(
+            rules[0].condition,
+            RuleCondition::Opaque("DOMAIN,example.com".to_string())
+        );
+        assert_matches!(rules[0].check(), Err(ConfigError::RuleInvalid { .. }));
+        assert!(rules[1].check().is_ok());
+        assert_eq!(
+            serde_yaml::to_string(&rules).expect("should serialize rules"),
+            "- DOMAIN,example.com\n- SCRIPT,quic,REJECT\n"
+        );
+    }
+}
@@ -0,0 +1,666 @@
+use super::{ClashConfig, Proxy};
+use crate::error::ConfigError;
+use base64::Engine;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use serde_yaml::{Mapping, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use url::Url;
+
+const SS_SCHEME: &str = "ss";
+const VMESS_SCHEME: &str = "vmess";
+const TROJAN_SCHEME: &str = "trojan";
+const VLESS_SCHEME: &str = "vless";
+const HYSTERIA2_SCHEME: &str = "hysteria2";
+const HY2_SCHEME: &str = "hy2";
+
+const SUPPORTED_SCHEMES: [&str; 6] = [
+    SS_SCHEME,
+    VMESS_SCHEME,
+    TROJAN_SCHEME,
+    VLESS_SCHEME,
+    HYSTERIA2_SCHEME,
+    HY2_SCHEME,
+];
+
+const BASE64_STANDARD_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+const BASE64_URL_SAFE_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionFormat {
+    ClashYaml,
+    ShareLinks,
+    Base64ShareLinks,
+}
+
+/// decode base64 in standard or url-safe alphabet, padding optional and whitespaces ignored
+pub fn decode_base64_lenient(input: &str) -> Option<Vec<u8>> {
+    let input = input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    BASE64_STANDARD_INDIFFERENT
+        .decode(&input)
+        .or_else(|_| BASE64_URL_SAFE_INDIFFERENT.decode(&input))
+        .ok()
+}
+
+fn decode_base64_string_lenient(input: &str) -> Option<String> {
+    decode_base64_lenient(input).and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+fn link_scheme(line: &str) -> Option<&str> {
+    line.split_once("://").map(|(scheme, _)| scheme)
+}
+
+fn looks_like_share_links(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .and_then(link_scheme)
+        .is_some_and(|scheme| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+}
+
+pub fn detect_subscription_format(content: &str) -> SubscriptionFormat {
+    let content = content.trim();
+    if looks_like_share_links(content) {
+        return SubscriptionFormat::ShareLinks;
+    }
+    if decode_base64_string_lenient(content).is_some_and(|decoded| looks_like_share_links(&decoded))
+    {
+        return SubscriptionFormat::Base64ShareLinks;
+    }
+    SubscriptionFormat::ClashYaml
+}
+
+/// parse subscription content served by a provider, which might be clash yaml,
+/// or a list of share links in plain text or base64
+pub fn parse_subscription_content(
+    config_name: &str,
+    content: &str,
+) -> Result<ClashConfig, ConfigError> {
+    match detect_subscription_format(content) {
+        SubscriptionFormat::ClashYaml => Ok(serde_yaml::from_str(content)?),
+        SubscriptionFormat::ShareLinks => parse_share_links(config_name, content),
+        SubscriptionFormat::Base64ShareLinks => {
+            let decoded = decode_base64_string_lenient(content.trim()).ok_or_else(|| {
+                anyhow::anyhow!("failed to decode base64 subscription of {}", config_name)
+            })?;
+            parse_share_links(config_name, &decoded)
+        }
+    }
+}
+
+/// malformed links are skipped with a warning, the source only fails when none of its links parse
+pub fn parse_share_links(config_name: &str, content: &str) -> Result<ClashConfig, ConfigError> {
+    let mut proxies = vec![];
+    let mut first_error = None;
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let scheme = link_scheme(line).unwrap_or_default();
+        if !SUPPORTED_SCHEMES.contains(&scheme) {
+            tracing::warn!(
+                "skip unsupported share link scheme {} from source config {}",
+                scheme,
+                config_name
+            );
+            continue;
+        }
+        match parse_share_link(config_name, line) {
+            Ok(proxy) => proxies.push(proxy),
+            Err(e) => {
+                tracing::warn!("skip {}", e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if proxies.is_empty()
+        && let Some(e) = first_error
+    {
+        return Err(e);
+    }
+    Ok(ClashConfig {
+        others: HashMap::new(),
+        proxies,
+        proxy_groups: vec![],
+        rules: vec![],
+    })
+}
+
+pub fn parse_share_link(config_name: &str, link: &str) -> Result<Proxy, ConfigError> {
+    let invalid = |reason: String| ConfigError::ShareLinkInvalid {
+        config_name: config_name.to_string(),
+        link: link.to_string(),
+        reason,
+    };
+
+    let link = link.trim();
+    let fields = match link_scheme(link) {
+        Some(SS_SCHEME) => parse_ss_link(link),
+        Some(VMESS_SCHEME) => parse_vmess_link(link),
+        Some(TROJAN_SCHEME) => parse_trojan_link(link),
+        Some(VLESS_SCHEME) => parse_vless_link(link),
+        Some(HYSTERIA2_SCHEME) | Some(HY2_SCHEME) => parse_hysteria2_link(link),
+        Some(scheme) => Err(format!("unsupported scheme {}", scheme)),
+        None => Err(String::from("missing scheme")),
+    }
+    .map_err(invalid)?;
+
+    serde_yaml::from_value(Value::Mapping(fields)).map_err(|e| invalid(e.to_string()))
+}
+
+fn insert<V: Into<Value>>(fields: &mut Mapping, key: &str, value: V) {
+    fields.insert(Value::String(key.to_string()), value.into());
+}
+
+fn insert_non_empty(fields: &mut Mapping, key: &str, value: Option<&str>) {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        insert(fields, key, value);
+    }
+}
+
+fn is_truthy(value: Option<&str>) -> bool {
+    matches!(value, Some("1") | Some("true"))
+}
+
+fn percent_decode(input: &str) -> String {
+    percent_encoding::percent_decode_str(input)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+struct LinkParts {
+    name: String,
+    userinfo: String,
+    server: String,
+    port: u16,
+    params: HashMap<String, String>,
+}
+
+impl LinkParts {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(|s| s as &str)
+    }
+}
+
+fn parse_link_parts(link: &str) -> Result<LinkParts, String> {
+    let url = Url::parse(link).map_err(|e| e.to_string())?;
+    let server = url
+        .host_str()
+        .filter(|h| !h.is_empty())
+        .ok_or_else(|| String::from("missing server"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = url.port().ok_or_else(|| String::from("missing port"))?;
+    let userinfo = match url.password() {
+        Some(password) => format!("{}:{}", url.username(), password),
+        None => url.username().to_string(),
+    };
+    let name = url
+        .fragment()
+        .map(percent_decode)
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("{}:{}", server, port));
+    Ok(LinkParts {
+        name,
+        userinfo: percent_decode(&userinfo),
+        server,
+        port,
+        params: url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect(),
+    })
+}
+
+fn base_fields(kind: &str, parts: &LinkParts) -> Mapping {
+    let mut fields = Mapping::new();
+    insert(&mut fields, "name", parts.name.as_str());
+    insert(&mut fields, "type", kind);
+    insert(&mut fields, "server", parts.server.as_str());
+    insert(&mut fields, "port", parts.port);
+    fields
+}
+
+/// shared transport options of vless / trojan links, following the v2rayN query conventions
+fn insert_transport_fields(fields: &mut Mapping, parts: &LinkParts) {
+    let network = parts.param("type").unwrap_or("tcp");
+    match network {
+        "ws" => {
+            let mut ws_opts = Mapping::new();
+            insert(&mut ws_opts, "path", parts.param("path").unwrap_or("/"));
+            if let Some(host) = parts.param("host").filter(|h| !h.is_empty()) {
+                let mut headers = Mapping::new();
+                insert(&mut headers, "Host", host);
+                insert(&mut ws_opts, "headers", headers);
+            }
+            insert(fields, "network", "ws");
+            insert(fields, "ws-opts", ws_opts);
+        }
+        "grpc" => {
+            let mut grpc_opts = Mapping::new();
+            insert_non_empty(
+                &mut grpc_opts,
+                "grpc-service-name",
+                parts.param("serviceName"),
+            );
+            insert(fields, "network", "grpc");
+            insert(fields, "grpc-opts", grpc_opts);
+        }
+        "tcp" => {}
+        other => insert(fields, "network", other),
+    }
+}
+
+fn parse_ss_link(link: &str) -> Result<Mapping, String> {
+    let body = &link[SS_SCHEME.len() + 3..];
+    let (body, fragment) = body.split_once('#').unwrap_or((body, ""));
+    let (body, query) = body.split_once('?').unwrap_or((body, ""));
+    let body = body.trim_end_matches('/');
+
+    // legacy format: ss://base64(method:password@server:port)#name
+    let body: Cow<str> = if body.contains('@') {
+        Cow::Borrowed(body)
+    } else {
+        Cow::Owned(
+            decode_base64_string_lenient(body)
+                .ok_or_else(|| String::from("invalid base64 payload"))?,
+        )
+    };
+
+    let (userinfo, host_port) = body
+        .rsplit_once('@')
+        .ok_or_else(|| String::from("missing userinfo"))?;
+    let normalized = format!(
+        "{}://{}{}{}",
+        SS_SCHEME,
+        host_port,
+        if query.is_empty() { "" } else { "?" },
+        query
+    );
+    let mut parts = parse_link_parts(&normalized)?;
+    if !fragment.is_empty() {
+        parts.name = percent_decode(fragment);
+    }
+
+    // SIP002 userinfo is base64(method:password), but some providers leave it in plain text
+    let userinfo = percent_decode(userinfo);
+    let userinfo = if userinfo.contains(':') {
+        userinfo
+    } else {
+        decode_base64_string_lenient(&userinfo)
+            .ok_or_else(|| String::from("invalid base64 userinfo"))?
+    };
+    let (cipher, password) = userinfo
+        .split_once(':')
+        .ok_or_else(|| String::from("missing cipher or password"))?;
+
+    let mut fields = base_fields(SS_SCHEME, &parts);
+    insert(&mut fields, "cipher", cipher);
+    insert(&mut fields, "password", password);
+    insert(&mut fields, "udp", true);
+
+    if let Some(plugin) = parts.param("plugin") {
+        let mut plugin_parts = plugin.split(';');
+        let plugin_name = plugin_parts.next().unwrap_or_default();
+        let plugin_params = plugin_parts
+            .map(|p| p.split_once('=').unwrap_or((p, "true")))
+            .collect::<HashMap<_, _>>();
+        let mut plugin_opts = Mapping::new();
+        match plugin_name {
+            "obfs-local" | "simple-obfs" => {
+                insert(&mut fields, "plugin", "obfs");
+                insert_non_empty(&mut plugin_opts, "mode", plugin_params.get("obfs").copied());
+                insert_non_empty(
+                    &mut plugin_opts,
+                    "host",
+                    plugin_params.get("obfs-host").copied(),
+                );
+            }
+            "v2ray-plugin" => {
+                insert(&mut fields, "plugin", "v2ray-plugin");
+                insert(
+                    &mut plugin_opts,
+                    "mode",
+                    plugin_params.get("mode").copied().unwrap_or("websocket"),
+                );
+                insert(&mut plugin_opts, "tls", plugin_params.contains_key("tls"));
+                insert_non_empty(&mut plugin_opts, "host", plugin_params.get("host").copied());
+                insert_non_empty(&mut plugin_opts, "path", plugin_params.get("path").copied());
+            }
+            other => return Err(format!("unsupported ss plugin {}", other)),
+        }
+        insert(&mut fields, "plugin-opts", plugin_opts);
+    }
+
+    Ok(fields)
+}
+
+fn json_str<'a>(json: &'a serde_json::Value, key: &str) -> Option<Cow<'a, str>> {
+    match json.get(key)? {
+        serde_json::Value::String(s) => Some(Cow::Borrowed(s)),
+        serde_json::Value::Number(n) => Some(Cow::Owned(n.to_string())),
+        _ => None,
+    }
+}
+
+/// vmess links follow the v2rayN format: vmess://base64(json)
+fn parse_vmess_link(link: &str) -> Result<Mapping, String> {
+    let body = &link[VMESS_SCHEME.len() + 3..];
+    let decoded =
+        decode_base64_string_lenient(body).ok_or_else(|| String::from("invalid base64 payload"))?;
+    let json: serde_json::Value = serde_json::from_str(&decoded).map_err(|e| e.to_string())?;
+
+    let server = json_str(&json, "add").ok_or_else(|| String::from("missing server"))?;
+    let port = json_str(&json, "port")
+        .and_then(|p| p.parse::<u16>().ok())
+        .ok_or_else(|| String::from("missing or invalid port"))?;
+    let uuid = json_str(&json, "id").ok_or_else(|| String::from("missing uuid"))?;
+    let name = json_str(&json, "ps")
+        .filter(|n| !n.is_empty())
+        .map(Cow::into_owned)
+        .unwrap_or_else(|| format!("{}:{}", server, port));
+    let alter_id = json_str(&json, "aid")
+        .and_then(|a| a.parse::<u32>().ok())
+        .unwrap_or_default();
+
+    let mut fields = Mapping::new();
+    insert(&mut fields, "name", name);
+    insert(&mut fields, "type", VMESS_SCHEME);
+    insert(&mut fields, "server", server.as_ref());
+    insert(&mut fields, "port", port);
+    insert(&mut fields, "uuid", uuid.as_ref());
+    insert(&mut fields, "alterId", alter_id);
+    insert(
+        &mut fields,
+        "cipher",
+        json_str(&json, "scy")
+            .filter(|c| !c.is_empty())
+            .as_deref()
+            .unwrap_or("auto"),
+    );
+    insert(&mut fields, "udp", true);
+
+    if json_str(&json, "tls").as_deref() == Some("tls") {
+        insert(&mut fields, "tls", true);
+        insert_non_empty(&mut fields, "servername", json_str(&json, "sni").as_deref());
+    }
+
+    let host = json_str(&json, "host");
+    let path = json_str(&json, "path");
+    match json_str(&json, "net").as_deref().unwrap_or("tcp") {
+        "ws" => {
+            let mut ws_opts = Mapping::new();
+            insert(&mut ws_opts, "path", path.as_deref().unwrap_or("/"));
+            if let Some(host) = host.filter(|h| !h.is_empty()) {
+                let mut headers = Mapping::new();
+                insert(&mut headers, "Host", host.as_ref());
+                insert(&mut ws_opts, "headers", headers);
+            }
+            insert(&mut fields, "network", "ws");
+            insert(&mut fields, "ws-opts", ws_opts);
+        }
+        "h2" => {
+            let mut h2_opts = Mapping::new();
+            insert(&mut h2_opts, "path", path.as_deref().unwrap_or("/"));
+            if let Some(host) = host.filter(|h| !h.is_empty()) {
+                insert(
+                    &mut h2_opts,
+                    "host",
+                    Value::Sequence(vec![Value::String(host.into_owned())]),
+                );
+            }
+            insert(&mut fields, "network", "h2");
+            insert(&mut fields, "h2-opts", h2_opts);
+        }
+        "grpc" => {
+            let mut grpc_opts = Mapping::new();
+            insert_non_empty(&mut grpc_opts, "grpc-service-name", path.as_deref());
+            insert(&mut fields, "network", "grpc");
+            insert(&mut fields, "grpc-opts", grpc_opts);
+        }
+        "tcp" => {}
+        other => insert(&mut fields, "network", other),
+    }
+
+    Ok(fields)
+}
+
+fn parse_trojan_link(link: &str) -> Result<Mapping, String> {
+    let parts = parse_link_parts(link)?;
+    if parts.userinfo.is_empty() {
+        return Err(String::from("missing password"));
+    }
+
+    let mut fields = base_fields(TROJAN_SCHEME, &parts);
+    insert(&mut fields, "password", parts.userinfo.as_str());
+    insert(&mut fields, "udp", true);
+    insert_non_empty(
+        &mut fields,
+        "sni",
+        parts.param("sni").or_else(|| parts.param("peer")),
+    );
+    if is_truthy(parts.param("allowInsecure")) {
+        insert(&mut fields, "skip-cert-verify", true);
+    }
+    insert_transport_fields(&mut fields, &parts);
+
+    Ok(fields)
+}
+
+fn parse_vless_link(link: &str) -> Result<Mapping, String> {
+    let parts = parse_link_parts(link)?;
+    if parts.userinfo.is_empty() {
+        return Err(String::from("missing uuid"));
+    }
+
+    let mut fields = base_fields(VLESS_SCHEME, &parts);
+    insert(&mut fields, "uuid", parts.userinfo.as_str());
+    insert(&mut fields, "udp", true);
+    insert_non_empty(&mut fields, "flow", parts.param("flow"));
+
+    match parts.param("security") {
+        Some("tls") | Some("reality") => {
+            insert(&mut fields, "tls", true);
+            insert_non_empty(&mut fields, "servername", parts.param("sni"));
+            insert_non_empty(&mut fields, "client-fingerprint", parts.param("fp"));
+            if parts.param("security") == Some("reality") {
+                let mut reality_opts = Mapping::new();
+                insert_non_empty(&mut reality_opts, "public-key", parts.param("pbk"));
+                insert_non_empty(&mut reality_opts, "short-id", parts.param("sid"));
+                insert(&mut fields, "reality-opts", reality_opts);
+            }
+        }
+        _ => {}
+    }
+    if is_truthy(parts.param("allowInsecure")) {
+        insert(&mut fields, "skip-cert-verify", true);
+    }
+    insert_transport_fields(&mut fields, &parts);
+
+    Ok(fields)
+}
+
+fn parse_hysteria2_link(link: &str) -> Result<Mapping, String> {
+    let parts = parse_link_parts(link)?;
+
+    let mut fields = base_fields(HYSTERIA2_SCHEME, &parts);
+    insert(&mut fields, "password", parts.userinfo.as_str());
+    insert_non_empty(&mut fields, "sni", parts.param("sni"));
+    if is_truthy(parts.param("insecure")) {
+        insert(&mut fields, "skip-cert-verify", true);
+    }
+    insert_non_empty(&mut fields, "obfs", parts.param("obfs"));
+    insert_non_empty(&mut fields, "obfs-password", parts.param("obfs-password"));
+    insert_non_empty(&mut fields, "ports", parts.param("mport"));
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use std::assert_matches;
+
+    fn str_field(proxy: &Proxy, key: &str) -> Option<String> {
+        serde_yaml::to_value(proxy)
+            .ok()?
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    }
+
+    #[test]
+    fn test_detect_subscription_format() {
+        let links =
+            "ss://YWVzLTEyOC1nY206cGFzcw@a.example.com:443#A\ntrojan://pass@b.example.com:443#B";
+        assert_eq!(
+            detect_subscription_format(links),
+            SubscriptionFormat::ShareLinks
+        );
+        assert_eq!(
+            detect_subscription_format(&STANDARD.encode(links)),
+            SubscriptionFormat::Base64ShareLinks
+        );
+        assert_eq!(
+            detect_subscription_format(include_str!("../tests/profile1.yaml")),
+            SubscriptionFormat::ClashYaml
+        );
+    }
+
+    #[test]
+    fn test_parse_ss_sip002_link() {
+        let proxy = parse_share_link(
+            "test",
+            "ss://YWVzLTEyOC1nY206dGVzdA@192.168.100.1:8888/?plugin=obfs-local%3Bobfs%3Dhttp%3Bobfs-host%3Dexample.com#Example%20SS",
+        )
+        .expect("should parse ss link");
+
+        assert_eq!(proxy.name(), "Example SS");
+        assert_eq!(proxy.server(), "192.168.100.1");
+        assert_eq!(str_field(&proxy, "cipher").as_deref(), Some("aes-128-gcm"));
+        assert_eq!(str_field(&proxy, "password").as_deref(), Some("test"));
+        assert_eq!(str_field(&proxy, "plugin").as_deref(), Some("obfs"));
+    }
+
+    #[test]
+    fn test_parse_ss_legacy_link() {
+        let payload = STANDARD.encode("chacha20-ietf-poly1305:pa:ss@ss.example.com:8388");
+        let proxy = parse_share_link("test", &format!("ss://{}#Legacy", payload))
+            .expect("should parse legacy ss link");
+
+        assert_eq!(proxy.name(), "Legacy");
+        assert_eq!(proxy.server(), "ss.example.com");
+        assert_eq!(
+            str_field(&proxy, "cipher").as_deref(),
+            Some("chacha20-ietf-poly1305")
+        );
+        assert_eq!(str_field(&proxy, "password").as_deref(), Some("pa:ss"));
+    }
+
+    #[test]
+    fn test_parse_vmess_link() {
+        let json = r#"{"v":"2","ps":"VMess WS","add":"vmess.example.com","port":"443","id":"b831381d-6324-4d53-ad4f-8cda48b30811","aid":0,"scy":"auto","net":"ws","type":"none","host":"cdn.example.com","path":"/ray","tls":"tls","sni":"cdn.example.com"}"#;
+        let proxy = parse_share_link("test", &format!("vmess://{}", STANDARD.encode(json)))
+            .expect("should parse vmess link");
+
+        assert_eq!(proxy.name(), "VMess WS");
+        assert_eq!(proxy.server(), "vmess.example.com");
+        assert_eq!(
+            str_field(&proxy, "uuid").as_deref(),
+            Some("b831381d-6324-4d53-ad4f-8cda48b30811")
+        );
+        assert_eq!(str_field(&proxy, "network").as_deref(), Some("ws"));
+        assert_eq!(
+            str_field(&proxy, "servername").as_deref(),
+            Some("cdn.example.com")
+        );
+    }
+
+    #[test]
+    fn test_parse_trojan_and_vless_link() {
+        let trojan = parse_share_link(
+            "test",
+            "trojan://p%40ss@trojan.example.com:443?sni=sni.example.com&allowInsecure=1#Trojan",
+        )
+        .expect("should parse trojan link");
+        assert_eq!(trojan.name(), "Trojan");
+        assert_eq!(str_field(&trojan, "password").as_deref(), Some("p@ss"));
+        assert_eq!(
+            str_field(&trojan, "sni").as_deref(),
+            Some("sni.example.com")
+        );
+
+        let vless = parse_share_link(
+            "test",
+            "vless://b831381d-6324-4d53-ad4f-8cda48b30811@[2001:db8::1]:443?security=reality&sni=www.example.com&fp=chrome&pbk=pubkey&sid=ab&type=grpc&serviceName=svc&flow=xtls-rprx-vision",
+        )
+        .expect("should parse vless link");
+        assert_eq!(vless.name(), "2001:db8::1:443");
+        assert_eq!(vless.server(), "2001:db8::1");
+        assert_eq!(
+            str_field(&vless, "flow").as_deref(),
+            Some("xtls-rprx-vision")
+        );
+        assert_eq!(str_field(&vless, "network").as_deref(), Some("grpc"));
+    }
+
+    #[test]
+    fn test_parse_hysteria2_link() {
+        let proxy = parse_share_link(
+            "test",
+            "hy2://secret@hy2.example.com:8443/?sni=hy2.example.com&obfs=salamander&obfs-password=x&insecure=1#HY2",
+        )
+        .expect("should parse hysteria2 link");
+
+        assert_matches!(&proxy, &Proxy::HysteriaV2(_));
+        assert_eq!(str_field(&proxy, "password").as_deref(), Some("secret"));
+        assert_eq!(str_field(&proxy, "auth").as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_parse_base64_subscription_content() -> anyhow::Result<()> {
+        let links = "trojan://pass@a.example.com:443#A\n\nunknown://whatever\nhysteria2://pass@b.example.com:443#B\n";
+        let config = parse_subscription_content("test", &STANDARD.encode(links))?;
+
+        assert_eq!(config.proxies.len(), 2usize);
+        assert_eq!(config.proxies[0].name(), "A");
+        assert_eq!(config.proxies[1].name(), "B");
+        assert!(config.proxy_groups.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_share_link() {
+        let res = parse_share_link("test", "trojan://@a.example.com:443#A");
+        assert_matches!(res, Err(ConfigError::ShareLinkInvalid { .. }));
+    }
+
+    #[test]
+    fn test_parse_share_links_skip_invalid() -> anyhow::Result<()> {
+        let links = "trojan://@a.example.com:443#A\ntrojan://pass@b.example.com:443#B\n";
+        let config = parse_share_links("test", links)?;
+        assert_eq!(config.proxies.len(), 1usize);
+        assert_eq!(config.proxies[0].name(), "B");
+
+        let res = parse_share_links("test", "trojan://@a.example.com:443#A\n");
+        assert_matches!(res, Err(ConfigError::ShareLinkInvalid { .. }));
+        Ok(())
+    }
+}
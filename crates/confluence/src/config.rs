@@ -1,3 +1,10 @@
+use crate::crypto::SecretKey;
+use crate::models::subscribe_source::FetchOverrides;
+use std::time::Duration;
+
+/// upper bound of `max_retries` in the fetch overrides of a subscribe source
+const MAX_OVERRIDE_RETRIES: u32 = 5;
+
 #[derive(Clone, Debug)]
 pub enum AuthConfig {
     JWT {
@@ -26,4 +33,142 @@ pub struct AppConfig {
     pub host: String,
     pub auth: AuthConfig,
     pub database_url: String,
+    pub fetch: FetchConfig,
+    /// master keys sealing secrets at rest, the first one seals
+    pub secret_keys: Vec<SecretKey>,
+}
+
+/// how subscribe sources are fetched, overridable per subscribe source
+#[derive(Clone, Debug)]
+pub struct FetchConfig {
+    pub connect_timeout: Duration,
+    /// between two reads of the response
+    pub read_timeout: Duration,
+    /// of a whole attempt, from connecting to the end of the body
+    pub total_timeout: Duration,
+    /// attempts after the first one, on timeouts and server errors
+    pub max_retries: u32,
+    /// doubled on every retry
+    pub retry_base_delay: Duration,
+    /// bytes of a response body
+    pub max_body_size: usize,
+    /// bytes of a subscription body, smaller ones are taken for error messages
+    pub min_body_size: usize,
+    /// trusted besides the built in roots
+    pub ca_certificates: Vec<reqwest::Certificate>,
+    pub accept_invalid_certs: bool,
+    /// let the proxy test reach loopback, link local and private hosts
+    pub allow_private_proxies: bool,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            total_timeout: Duration::from_secs(60),
+            max_retries: 2,
+            retry_base_delay: Duration::from_millis(500),
+            max_body_size: 16 * 1024 * 1024,
+            min_body_size: 32,
+            ca_certificates: vec![],
+            accept_invalid_certs: false,
+            allow_private_proxies: false,
+        }
+    }
+}
+
+impl FetchConfig {
+    /// overrides are clamped to the server maximums, timeouts to the total timeout,
+    /// retries to a fixed bound and the body size to the server limit
+    pub fn with_overrides(&self, overrides: Option<&FetchOverrides>) -> Self {
+        let mut config = self.clone();
+        let Some(overrides) = overrides else {
+            return config;
+        };
+        if let Some(secs) = overrides.connect_timeout_secs {
+            config.connect_timeout = Duration::from_secs(secs.into()).min(self.total_timeout);
+        }
+        if let Some(secs) = overrides.read_timeout_secs {
+            config.read_timeout = Duration::from_secs(secs.into()).min(self.total_timeout);
+        }
+        if let Some(max_retries) = overrides.max_retries {
+            config.max_retries = max_retries.min(MAX_OVERRIDE_RETRIES);
+        }
+        if let Some(max_body_size) = overrides.max_body_size {
+            config.max_body_size = (max_body_size as usize).min(self.max_body_size);
+        }
+        config
+    }
+
+    /// reject overrides past the server maximums before they are stored
+    pub fn check_overrides(&self, overrides: &FetchOverrides) -> Result<(), String> {
+        let max_timeout_secs = self.total_timeout.as_secs();
+        for (name, secs) in [
+            ("connect_timeout_secs", overrides.connect_timeout_secs),
+            ("read_timeout_secs", overrides.read_timeout_secs),
+        ] {
+            if secs.is_some_and(|secs| u64::from(secs) > max_timeout_secs) {
+                return Err(format!("{} exceeds {}", name, max_timeout_secs));
+            }
+        }
+        if overrides
+            .max_retries
+            .is_some_and(|max_retries| max_retries > MAX_OVERRIDE_RETRIES)
+        {
+            return Err(format!("max_retries exceeds {}", MAX_OVERRIDE_RETRIES));
+        }
+        if overrides
+            .max_body_size
+            .is_some_and(|max_body_size| max_body_size as usize > self.max_body_size)
+        {
+            return Err(format!("max_body_size exceeds {}", self.max_body_size));
+        }
+        Ok(())
+    }
+
+    /// the delay before a retry, exponential in the attempt with up to 50% jitter either way
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .retry_base_delay
+            .saturating_mul(2u32.saturating_pow(attempt));
+        delay.mul_f64(0.5 + fastrand::f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_config() {
+        let config = FetchConfig::default().with_overrides(Some(&FetchOverrides {
+            read_timeout_secs: Some(5),
+            max_retries: Some(0),
+            ..Default::default()
+        }));
+        assert_eq!(config.read_timeout, Duration::from_secs(5));
+        assert_eq!(config.max_retries, 0);
+        assert_eq!(config.connect_timeout, Duration::from_secs(10));
+
+        let overrides = FetchOverrides {
+            read_timeout_secs: Some(3600),
+            max_retries: Some(100),
+            max_body_size: Some(u32::MAX),
+            ..Default::default()
+        };
+        let defaults = FetchConfig::default();
+        assert!(defaults.check_overrides(&overrides).is_err());
+        assert!(defaults.check_overrides(&FetchOverrides::default()).is_ok());
+        let clamped = defaults.with_overrides(Some(&overrides));
+        assert_eq!(clamped.read_timeout, defaults.total_timeout);
+        assert_eq!(clamped.max_retries, MAX_OVERRIDE_RETRIES);
+        assert_eq!(clamped.max_body_size, defaults.max_body_size);
+
+        for attempt in 0..4 {
+            let base = config.retry_base_delay * 2u32.pow(attempt);
+            let delay = config.backoff(attempt);
+            assert!(delay >= base / 2 && delay <= base * 3 / 2);
+        }
+    }
 }
@@ -0,0 +1,53 @@
+use crate::clash::Proxy;
+use serde_yaml::{Mapping, Value as YamlValue};
+
+/// clash fields of a proxy as a yaml mapping, so that every target reads the same clash keys
+pub fn proxy_fields(proxy: &Proxy) -> Option<Mapping> {
+    match serde_yaml::to_value(proxy).ok()? {
+        YamlValue::Mapping(fields) => Some(fields),
+        _ => None,
+    }
+}
+
+pub fn proxy_type(proxy: &Proxy) -> String {
+    proxy.kind().to_string()
+}
+
+pub fn get<'a>(fields: &'a Mapping, key: &str) -> Option<&'a YamlValue> {
+    fields.get(key)
+}
+
+pub fn get_str<'a>(fields: &'a Mapping, key: &str) -> Option<&'a str> {
+    get(fields, key).and_then(YamlValue::as_str)
+}
+
+pub fn get_u64(fields: &Mapping, key: &str) -> Option<u64> {
+    match get(fields, key)? {
+        YamlValue::Number(n) => n.as_u64(),
+        YamlValue::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn get_bool(fields: &Mapping, key: &str) -> bool {
+    get(fields, key)
+        .and_then(YamlValue::as_bool)
+        .unwrap_or(false)
+}
+
+pub fn get_mapping<'a>(fields: &'a Mapping, key: &str) -> Option<&'a Mapping> {
+    get(fields, key).and_then(YamlValue::as_mapping)
+}
+
+/// parse bandwidth such as `100`, `100 Mbps` or `100m` into mbps
+pub fn get_mbps(fields: &Mapping, key: &str) -> Option<u64> {
+    match get(fields, key)? {
+        YamlValue::Number(n) => n.as_u64(),
+        YamlValue::String(s) => s
+            .trim()
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c.is_whitespace())
+            .parse()
+            .ok(),
+        _ => None,
+    }
+}
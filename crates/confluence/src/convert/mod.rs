@@ -0,0 +1,134 @@
+pub mod fields;
+pub mod quantumultx;
+pub mod singbox;
+pub mod stash;
+pub mod surge;
+
+use crate::clash::{ClashConfig, ProxyGroup};
+use crate::error::ConfigError;
+use crate::models::profile::ProfileTarget;
+use std::collections::HashSet;
+
+pub use quantumultx::to_quantumultx_config;
+pub use singbox::to_singbox_config;
+pub use stash::to_stash_config;
+pub use surge::to_surge_config;
+
+impl ProfileTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProfileTarget::Clash => "clash",
+            ProfileTarget::SingBox => "sing-box",
+            ProfileTarget::Surge => "surge",
+            ProfileTarget::Stash => "stash",
+            ProfileTarget::QuantumultX => "quantumult-x",
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ProfileTarget::Clash | ProfileTarget::Stash => "Confluence.yaml",
+            ProfileTarget::SingBox => "Confluence.json",
+            ProfileTarget::Surge | ProfileTarget::QuantumultX => "Confluence.conf",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ProfileTarget::SingBox => "application/json; charset=utf-8",
+            ProfileTarget::Surge | ProfileTarget::QuantumultX => "text/plain; charset=utf-8",
+            ProfileTarget::Clash | ProfileTarget::Stash => {
+                "application/octet-stream; charset=utf-8"
+            }
+        }
+    }
+
+    /// whether the profile format accepts `#` line comments, which carry the diagnostics
+    fn supports_line_comment(&self) -> bool {
+        !matches!(self, ProfileTarget::SingBox)
+    }
+}
+
+/// names with separators of the `name = value, ...` lines of surge and quantumult x profiles
+/// are reported and dropped, returns whether the name can be written
+fn check_line_name(name: &str, target: &ProfileTarget, diagnostics: &mut Vec<ConfigError>) -> bool {
+    if name.contains([',', '=']) {
+        diagnostics.push(ConfigError::NameUnsupported {
+            name: name.to_string(),
+            target: target.name().to_string(),
+        });
+        return false;
+    }
+    true
+}
+
+/// drop groups left without members once the `dropped` proxies and groups are removed,
+/// until no other group empties out, line based profiles reject empty groups
+fn drop_emptied_groups<'a>(
+    groups: &[&'a ProxyGroup],
+    dropped: &mut HashSet<&'a str>,
+    target: &ProfileTarget,
+    diagnostics: &mut Vec<ConfigError>,
+) {
+    loop {
+        let emptied = groups
+            .iter()
+            .filter(|g| {
+                !dropped.contains(g.name.as_str())
+                    && g.proxies.iter().all(|m| dropped.contains(m.as_str()))
+            })
+            .collect::<Vec<_>>();
+        if emptied.is_empty() {
+            return;
+        }
+        for g in emptied {
+            dropped.insert(&g.name);
+            diagnostics.push(ConfigError::ProxyGroupUnsupported {
+                group_name: g.name.clone(),
+                group_type: g.kind.name().to_string(),
+                target: target.name().to_string(),
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RenderedProfile {
+    pub content: String,
+    /// proxies, groups and rules which can not be expressed in the target format
+    pub diagnostics: Vec<ConfigError>,
+}
+
+/// render the muxed clash yaml content for the profile target client
+pub fn render_profile(
+    mux_content: &str,
+    target: ProfileTarget,
+) -> Result<RenderedProfile, ConfigError> {
+    let config = || serde_yaml::from_str::<ClashConfig>(mux_content);
+    let mut diagnostics = vec![];
+    let content = match target {
+        ProfileTarget::Clash => mux_content.to_string(),
+        ProfileTarget::SingBox => {
+            let profile = to_singbox_config(&config()?, &mut diagnostics)?;
+            serde_json::to_string_pretty(&profile).map_err(|e| ConfigError::Other(e.into()))?
+        }
+        ProfileTarget::Surge => to_surge_config(&config()?, &mut diagnostics)?,
+        ProfileTarget::Stash => to_stash_config(&config()?, &mut diagnostics)?,
+        ProfileTarget::QuantumultX => to_quantumultx_config(&config()?, &mut diagnostics)?,
+    };
+
+    let content = if target.supports_line_comment() && !diagnostics.is_empty() {
+        diagnostics
+            .iter()
+            .map(|d| format!("# {}\n", d))
+            .chain(std::iter::once(content))
+            .collect()
+    } else {
+        content
+    };
+
+    Ok(RenderedProfile {
+        content,
+        diagnostics,
+    })
+}
@@ -0,0 +1,363 @@
+use super::fields::{get_bool, get_mapping, get_str, get_u64, proxy_fields, proxy_type};
+use super::{check_line_name, drop_emptied_groups};
+use crate::clash::{ClashConfig, Proxy, ProxyGroupKind, Rule};
+use crate::error::ConfigError;
+use crate::models::profile::ProfileTarget;
+use serde_yaml::Mapping;
+use std::collections::HashSet;
+
+const TARGET: ProfileTarget = ProfileTarget::QuantumultX;
+
+/// quantumult x spells the builtin policies in lower case
+fn policy_name(name: &str) -> &str {
+    match name {
+        "DIRECT" => "direct",
+        "REJECT" | "REJECT-DROP" => "reject",
+        name => name,
+    }
+}
+
+/// map clash websocket / tls settings to the quantumult x `obfs` parameters,
+/// returns `None` for transports quantumult x can not express
+fn obfs_params(params: &mut Vec<String>, fields: &Mapping, tls: bool, sni_key: &str) -> Option<()> {
+    let sni = get_str(fields, sni_key).or_else(|| get_str(fields, "sni"));
+    match get_str(fields, "network") {
+        None | Some("tcp") => {
+            if tls {
+                params.push(String::from("obfs=over-tls"));
+                if let Some(sni) = sni {
+                    params.push(format!("obfs-host={}", sni));
+                }
+            }
+        }
+        Some("ws") => {
+            params.push(format!("obfs={}", if tls { "wss" } else { "ws" }));
+            let ws_opts = get_mapping(fields, "ws-opts");
+            let host = ws_opts
+                .and_then(|o| get_mapping(o, "headers"))
+                .and_then(|h| get_str(h, "Host"))
+                .or(sni);
+            if let Some(host) = host {
+                params.push(format!("obfs-host={}", host));
+            }
+            if let Some(path) = ws_opts.and_then(|o| get_str(o, "path")) {
+                params.push(format!("obfs-uri={}", path));
+            }
+        }
+        Some(_) => return None,
+    }
+    if tls && get_bool(fields, "skip-cert-verify") {
+        params.push(String::from("tls-verification=false"));
+    }
+    Some(())
+}
+
+/// convert a clash proxy into a quantumult x `[server_local]` line,
+/// returns `None` if the proxy is unsupported
+pub fn proxy_to_line(proxy: &Proxy) -> Option<String> {
+    let fields = &proxy_fields(proxy)?;
+    let port = get_u64(fields, "port")?;
+    let mut params = vec![];
+
+    let kind = match get_str(fields, "type")? {
+        "ss" => {
+            params.push(format!("method={}", get_str(fields, "cipher")?));
+            params.push(format!("password={}", get_str(fields, "password")?));
+            match get_str(fields, "plugin") {
+                None => {}
+                Some("obfs") => {
+                    let plugin_opts = get_mapping(fields, "plugin-opts")?;
+                    params.push(format!("obfs={}", get_str(plugin_opts, "mode")?));
+                    if let Some(host) = get_str(plugin_opts, "host") {
+                        params.push(format!("obfs-host={}", host));
+                    }
+                }
+                Some("v2ray-plugin") => {
+                    let plugin_opts = get_mapping(fields, "plugin-opts")?;
+                    let tls = get_bool(plugin_opts, "tls");
+                    params.push(format!("obfs={}", if tls { "wss" } else { "ws" }));
+                    if let Some(host) = get_str(plugin_opts, "host") {
+                        params.push(format!("obfs-host={}", host));
+                    }
+                    if let Some(path) = get_str(plugin_opts, "path") {
+                        params.push(format!("obfs-uri={}", path));
+                    }
+                }
+                Some(_) => return None,
+            }
+            if get_bool(fields, "udp") {
+                params.push(String::from("udp-relay=true"));
+            }
+            "shadowsocks"
+        }
+        "vmess" => {
+            let method = match get_str(fields, "cipher").unwrap_or("auto") {
+                "auto" => "chacha20-ietf-poly1305",
+                method => method,
+            };
+            params.push(format!("method={}", method));
+            params.push(format!("password={}", get_str(fields, "uuid")?));
+            obfs_params(&mut params, fields, get_bool(fields, "tls"), "servername")?;
+            if get_u64(fields, "alterId").unwrap_or_default() > 0 {
+                params.push(String::from("aead=false"));
+            }
+            "vmess"
+        }
+        "vless" => {
+            if get_mapping(fields, "reality-opts").is_some() || get_str(fields, "flow").is_some() {
+                return None;
+            }
+            params.push(String::from("method=none"));
+            params.push(format!("password={}", get_str(fields, "uuid")?));
+            obfs_params(&mut params, fields, get_bool(fields, "tls"), "servername")?;
+            "vless"
+        }
+        "trojan" => {
+            params.push(format!("password={}", get_str(fields, "password")?));
+            match get_str(fields, "network") {
+                None | Some("tcp") => {
+                    params.push(String::from("over-tls=true"));
+                    if let Some(sni) = get_str(fields, "sni") {
+                        params.push(format!("tls-host={}", sni));
+                    }
+                    if get_bool(fields, "skip-cert-verify") {
+                        params.push(String::from("tls-verification=false"));
+                    }
+                }
+                _ => obfs_params(&mut params, fields, true, "sni")?,
+            }
+            "trojan"
+        }
+        kind @ ("socks5" | "http") => {
+            if let Some(username) = get_str(fields, "username") {
+                params.push(format!("username={}", username));
+            }
+            if let Some(password) = get_str(fields, "password") {
+                params.push(format!("password={}", password));
+            }
+            if get_bool(fields, "tls") {
+                params.push(String::from("over-tls=true"));
+                if get_bool(fields, "skip-cert-verify") {
+                    params.push(String::from("tls-verification=false"));
+                }
+            }
+            kind
+        }
+        _ => return None,
+    };
+
+    let mut line = format!("{}={}:{}", kind, proxy.server(), port);
+    for param in params {
+        line.push_str(", ");
+        line.push_str(&param);
+    }
+    line.push_str(&format!(", tag={}", proxy.name()));
+    Some(line)
+}
+
+/// convert a clash rule into a quantumult x `[filter_local]` line,
+/// returns `None` if the rule is unsupported
+pub fn rule_to_line(rule: &Rule) -> Option<String> {
+    let target = policy_name(&rule.target);
+    let kind = match rule.condition.kind() {
+        "MATCH" => return Some(format!("final, {}", target)),
+        "DOMAIN" => "host",
+        "DOMAIN-SUFFIX" => "host-suffix",
+        "DOMAIN-KEYWORD" => "host-keyword",
+        "IP-CIDR" => "ip-cidr",
+        "IP-CIDR6" => "ip6-cidr",
+        "GEOIP" => "geoip",
+        "IP-ASN" => "ip-asn",
+        _ => return None,
+    };
+    Some(format!(
+        "{}, {}, {}",
+        kind,
+        rule.condition.payload(),
+        target
+    ))
+}
+
+/// render the muxed clash config as a quantumult x profile
+pub fn to_quantumultx_config(
+    config: &ClashConfig,
+    diagnostics: &mut Vec<ConfigError>,
+) -> Result<String, ConfigError> {
+    let mut dropped = HashSet::<&str>::new();
+    let mut server_lines = vec![];
+    for proxy in &config.proxies {
+        if !check_line_name(proxy.name(), &TARGET, diagnostics) {
+            dropped.insert(proxy.name());
+        } else if let Some(line) = proxy_to_line(proxy) {
+            server_lines.push(line);
+        } else {
+            dropped.insert(proxy.name());
+            diagnostics.push(ConfigError::ProxyUnsupported {
+                proxy_name: proxy.name().to_string(),
+                proxy_type: proxy_type(proxy),
+                target: TARGET.name().to_string(),
+            });
+        }
+    }
+
+    let mut policy_lines = vec![];
+    let groups = config
+        .proxy_groups
+        .iter()
+        .filter_map(|g| {
+            if !check_line_name(&g.name, &TARGET, diagnostics) {
+                dropped.insert(&g.name);
+                return None;
+            }
+            let kind = match &g.kind {
+                ProxyGroupKind::Select => Some("static"),
+                ProxyGroupKind::UrlTest => Some("url-latency-benchmark"),
+                ProxyGroupKind::Fallback => Some("available"),
+                ProxyGroupKind::LoadBalance => Some("round-robin"),
+                ProxyGroupKind::Other(_) => None,
+            };
+            if kind.is_none() {
+                dropped.insert(&g.name);
+                diagnostics.push(ConfigError::ProxyGroupUnsupported {
+                    group_name: g.name.clone(),
+                    group_type: g.kind.name().to_string(),
+                    target: TARGET.name().to_string(),
+                });
+            }
+            kind.map(|kind| (kind, g))
+        })
+        .collect::<Vec<_>>();
+    drop_emptied_groups(
+        &groups.iter().map(|(_, g)| *g).collect::<Vec<_>>(),
+        &mut dropped,
+        &TARGET,
+        diagnostics,
+    );
+
+    for (kind, group) in groups
+        .into_iter()
+        .filter(|(_, g)| !dropped.contains(g.name.as_str()))
+    {
+        let mut line = format!("{}={}", kind, group.name);
+        for member in group
+            .proxies
+            .iter()
+            .filter(|p| !dropped.contains(p.as_str()))
+        {
+            line.push_str(", ");
+            line.push_str(policy_name(member));
+        }
+        if let Some(interval) = group.others.get("interval").and_then(|v| v.as_u64()) {
+            line.push_str(&format!(", check-interval={}", interval));
+        }
+        if let Some(tolerance) = group.others.get("tolerance").and_then(|v| v.as_u64()) {
+            line.push_str(&format!(", tolerance={}", tolerance));
+        }
+        policy_lines.push(line);
+    }
+
+    let mut filter_lines = vec![];
+    for rule in &config.rules {
+        let line = match dropped.contains(rule.target.as_str()) {
+            true => None,
+            false => rule_to_line(rule),
+        };
+        match line {
+            Some(line) => filter_lines.push(line),
+            None => diagnostics.push(ConfigError::RuleUnsupported {
+                rule: rule.to_string(),
+                target: TARGET.name().to_string(),
+            }),
+        }
+    }
+
+    let general_lines = vec![String::from(
+        "excluded_routes = 127.0.0.0/8, 192.168.0.0/16, 10.0.0.0/8, 172.16.0.0/12",
+    )];
+
+    let mut content = String::new();
+    for (section, lines) in [
+        ("general", general_lines),
+        ("server_local", server_lines),
+        ("policy", policy_lines),
+        ("filter_local", filter_lines),
+    ] {
+        content.push_str(&format!("[{}]\n", section));
+        for line in lines {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clash::RuleCondition;
+    use crate::mux::{MuxSource, mux_configs};
+
+    #[test]
+    fn test_to_quantumultx_config() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let config2: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile2.yaml"))?;
+        let tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let sources = vec![
+            MuxSource::new("proxy1", config1),
+            MuxSource::new("proxy2", config2),
+        ];
+        let mut config = mux_configs("test", &tmpl, &sources, &[])?;
+        config.proxies.push(serde_yaml::from_str(
+            "{ name: H, type: hysteria2, server: h.example.com, port: 443, password: x }",
+        )?);
+        config.rules.push(Rule::new(RuleCondition::Match, "PROXY"));
+
+        let mut diagnostics = vec![];
+        let profile = to_quantumultx_config(&config, &mut diagnostics)?;
+
+        assert!(profile.contains("static=PROXY, SPEED, QUANTITY, direct, proxy1, proxy2, reject"));
+        assert!(profile.contains("shadowsocks=tests.proxy1.com:22008, method=aes-128-gcm"));
+        assert!(profile.contains("host-suffix, proxy1.com, direct"));
+        assert!(profile.contains("final, PROXY"));
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::ProxyUnsupported { proxy_name, .. } if proxy_name == "H"
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_quantumultx_config_emptied_groups() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: "A, B", type: socks5, server: a.example.com, port: 1 }
+  - { name: S, type: ss, server: s.example.com, port: 1, cipher: aes-128-gcm, password: x }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [EMPTIED, S] }
+  - { name: EMPTIED, type: select, proxies: ["A, B"] }
+rules:
+  - MATCH,EMPTIED
+            "#,
+        )?;
+
+        let mut diagnostics = vec![];
+        let profile = to_quantumultx_config(&config, &mut diagnostics)?;
+
+        assert!(profile.contains("static=PROXY, S\n"));
+        assert!(!profile.contains("EMPTIED"));
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::ProxyGroupUnsupported { group_name, .. } if group_name == "EMPTIED"
+        )));
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::NameUnsupported { name, .. } if name == "A, B"
+        )));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,504 @@
+use super::fields::{
+    get, get_bool, get_mapping, get_mbps, get_str, get_u64, proxy_fields, proxy_type,
+};
+use crate::clash::{ClashConfig, Proxy, ProxyGroupKind, Rule, RuleCondition};
+use crate::error::ConfigError;
+use crate::models::profile::ProfileTarget;
+use serde_json::{Map, Value as JsonValue, json};
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::collections::HashSet;
+
+const TARGET: ProfileTarget = ProfileTarget::SingBox;
+pub const DIRECT_TAG: &str = "DIRECT";
+pub const REJECT_TAG: &str = "REJECT";
+const DEFAULT_MIXED_PORT: u64 = 7890;
+const DEFAULT_URL_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+
+fn yaml_to_json(value: &YamlValue) -> JsonValue {
+    serde_json::to_value(value).unwrap_or(JsonValue::Null)
+}
+
+fn insert_some(
+    outbound: &mut Map<String, JsonValue>,
+    key: &str,
+    value: Option<impl Into<JsonValue>>,
+) {
+    if let Some(value) = value {
+        outbound.insert(key.to_string(), value.into());
+    }
+}
+
+fn tls_options(fields: &Mapping, force: bool, server_name_key: &str) -> Option<JsonValue> {
+    if !force && !get_bool(fields, "tls") {
+        return None;
+    }
+    let mut tls = Map::new();
+    tls.insert("enabled".to_string(), JsonValue::Bool(true));
+    insert_some(
+        &mut tls,
+        "server_name",
+        get_str(fields, server_name_key).or_else(|| get_str(fields, "sni")),
+    );
+    if get_bool(fields, "skip-cert-verify") {
+        tls.insert("insecure".to_string(), JsonValue::Bool(true));
+    }
+    if let Some(alpn) = get(fields, "alpn") {
+        tls.insert(
+            "alpn".to_string(),
+            match alpn {
+                YamlValue::String(s) => json!([s]),
+                other => yaml_to_json(other),
+            },
+        );
+    }
+    if let Some(fingerprint) = get_str(fields, "client-fingerprint") {
+        tls.insert(
+            "utls".to_string(),
+            json!({ "enabled": true, "fingerprint": fingerprint }),
+        );
+    }
+    if let Some(reality) = get_mapping(fields, "reality-opts") {
+        let mut reality_opts = Map::new();
+        reality_opts.insert("enabled".to_string(), JsonValue::Bool(true));
+        insert_some(
+            &mut reality_opts,
+            "public_key",
+            get_str(reality, "public-key"),
+        );
+        insert_some(&mut reality_opts, "short_id", get_str(reality, "short-id"));
+        tls.insert("reality".to_string(), JsonValue::Object(reality_opts));
+    }
+    Some(JsonValue::Object(tls))
+}
+
+fn transport_options(fields: &Mapping) -> Option<JsonValue> {
+    match get_str(fields, "network")? {
+        "ws" => {
+            let mut transport = Map::new();
+            transport.insert("type".to_string(), json!("ws"));
+            if let Some(ws_opts) = get_mapping(fields, "ws-opts") {
+                insert_some(&mut transport, "path", get_str(ws_opts, "path"));
+                insert_some(
+                    &mut transport,
+                    "headers",
+                    get(ws_opts, "headers").map(yaml_to_json),
+                );
+            }
+            Some(JsonValue::Object(transport))
+        }
+        "grpc" => {
+            let mut transport = Map::new();
+            transport.insert("type".to_string(), json!("grpc"));
+            insert_some(
+                &mut transport,
+                "service_name",
+                get_mapping(fields, "grpc-opts").and_then(|o| get_str(o, "grpc-service-name")),
+            );
+            Some(JsonValue::Object(transport))
+        }
+        "h2" => {
+            let mut transport = Map::new();
+            transport.insert("type".to_string(), json!("http"));
+            if let Some(h2_opts) = get_mapping(fields, "h2-opts") {
+                insert_some(&mut transport, "path", get_str(h2_opts, "path"));
+                insert_some(
+                    &mut transport,
+                    "host",
+                    get(h2_opts, "host").map(yaml_to_json),
+                );
+            }
+            Some(JsonValue::Object(transport))
+        }
+        _ => None,
+    }
+}
+
+/// convert a clash proxy into a sing-box outbound, returns `None` if the proxy type is unsupported
+pub fn proxy_to_outbound(proxy: &Proxy) -> Option<JsonValue> {
+    let fields = &proxy_fields(proxy)?;
+
+    let mut outbound = Map::new();
+    let kind = match get_str(fields, "type")? {
+        "ss" => {
+            insert_some(&mut outbound, "method", get_str(fields, "cipher"));
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            if let Some(plugin) = get_str(fields, "plugin") {
+                let plugin_opts = get_mapping(fields, "plugin-opts");
+                let opt = |key: &str| plugin_opts.and_then(|o| get_str(o, key));
+                let (plugin, opts) = match plugin {
+                    "obfs" => (
+                        "obfs-local",
+                        [
+                            opt("mode").map(|m| format!("obfs={}", m)),
+                            opt("host").map(|h| format!("obfs-host={}", h)),
+                        ],
+                    ),
+                    "v2ray-plugin" => (
+                        "v2ray-plugin",
+                        [
+                            opt("host").map(|h| format!("host={}", h)),
+                            opt("path").map(|p| format!("path={}", p)),
+                        ],
+                    ),
+                    _ => return None,
+                };
+                outbound.insert("plugin".to_string(), json!(plugin));
+                outbound.insert(
+                    "plugin_opts".to_string(),
+                    json!(opts.into_iter().flatten().collect::<Vec<_>>().join(";")),
+                );
+            }
+            "shadowsocks"
+        }
+        "vmess" => {
+            insert_some(&mut outbound, "uuid", get_str(fields, "uuid"));
+            insert_some(&mut outbound, "alter_id", get_u64(fields, "alterId"));
+            insert_some(&mut outbound, "security", get_str(fields, "cipher"));
+            insert_some(
+                &mut outbound,
+                "tls",
+                tls_options(fields, false, "servername"),
+            );
+            insert_some(&mut outbound, "transport", transport_options(fields));
+            "vmess"
+        }
+        "vless" => {
+            insert_some(&mut outbound, "uuid", get_str(fields, "uuid"));
+            insert_some(&mut outbound, "flow", get_str(fields, "flow"));
+            insert_some(
+                &mut outbound,
+                "tls",
+                tls_options(fields, false, "servername"),
+            );
+            insert_some(&mut outbound, "transport", transport_options(fields));
+            "vless"
+        }
+        "trojan" => {
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            insert_some(&mut outbound, "tls", tls_options(fields, true, "sni"));
+            insert_some(&mut outbound, "transport", transport_options(fields));
+            "trojan"
+        }
+        "hysteria2" => {
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            insert_some(&mut outbound, "up_mbps", get_mbps(fields, "up"));
+            insert_some(&mut outbound, "down_mbps", get_mbps(fields, "down"));
+            if let Some(obfs) = get_str(fields, "obfs") {
+                outbound.insert(
+                    "obfs".to_string(),
+                    json!({ "type": obfs, "password": get_str(fields, "obfs-password") }),
+                );
+            }
+            insert_some(&mut outbound, "tls", tls_options(fields, true, "sni"));
+            "hysteria2"
+        }
+        "hysteria" => {
+            insert_some(&mut outbound, "auth_str", get_str(fields, "auth-str"));
+            insert_some(&mut outbound, "up_mbps", get_mbps(fields, "up"));
+            insert_some(&mut outbound, "down_mbps", get_mbps(fields, "down"));
+            insert_some(&mut outbound, "obfs", get_str(fields, "obfs"));
+            insert_some(&mut outbound, "tls", tls_options(fields, true, "sni"));
+            "hysteria"
+        }
+        "tuic" => {
+            insert_some(&mut outbound, "uuid", get_str(fields, "uuid"));
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            insert_some(
+                &mut outbound,
+                "congestion_control",
+                get_str(fields, "congestion-controller"),
+            );
+            insert_some(&mut outbound, "tls", tls_options(fields, true, "sni"));
+            "tuic"
+        }
+        "socks5" => {
+            outbound.insert("version".to_string(), json!("5"));
+            insert_some(&mut outbound, "username", get_str(fields, "username"));
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            "socks"
+        }
+        "http" => {
+            insert_some(&mut outbound, "username", get_str(fields, "username"));
+            insert_some(&mut outbound, "password", get_str(fields, "password"));
+            insert_some(&mut outbound, "tls", tls_options(fields, false, "sni"));
+            "http"
+        }
+        _ => return None,
+    };
+
+    outbound.insert("type".to_string(), json!(kind));
+    outbound.insert("tag".to_string(), json!(proxy.name()));
+    outbound.insert("server".to_string(), json!(proxy.server()));
+    insert_some(&mut outbound, "server_port", get_u64(fields, "port"));
+
+    Some(JsonValue::Object(outbound))
+}
+
+/// convert a clash rule condition into the matching fields of a sing-box route rule,
+/// logical rules become sing-box logical rules
+fn condition_to_route_rule(condition: &RuleCondition) -> Option<Map<String, JsonValue>> {
+    let (key, value) = match condition {
+        RuleCondition::Domain(v) => ("domain", json!([v])),
+        RuleCondition::DomainSuffix(v) => ("domain_suffix", json!([v])),
+        RuleCondition::DomainKeyword(v) => ("domain_keyword", json!([v])),
+        RuleCondition::IpCidr(cidr) | RuleCondition::IpCidr6(cidr) => {
+            ("ip_cidr", json!([cidr.to_string()]))
+        }
+        RuleCondition::ProcessName(v) => ("process_name", json!([v])),
+        RuleCondition::GeoIp(v)
+            if v.eq_ignore_ascii_case("LAN") || v.eq_ignore_ascii_case("private") =>
+        {
+            ("ip_is_private", json!(true))
+        }
+        RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
+            let rules = conditions
+                .iter()
+                .map(|c| condition_to_route_rule(c).map(JsonValue::Object))
+                .collect::<Option<Vec<_>>>()?;
+            let mode = match condition {
+                RuleCondition::And(_) => "and",
+                _ => "or",
+            };
+            let mut route_rule = Map::new();
+            route_rule.insert("type".to_string(), json!("logical"));
+            route_rule.insert("mode".to_string(), json!(mode));
+            route_rule.insert("rules".to_string(), json!(rules));
+            return Some(route_rule);
+        }
+        RuleCondition::Not(condition) => {
+            let mut route_rule = condition_to_route_rule(condition)?;
+            route_rule.insert("invert".to_string(), json!(true));
+            return Some(route_rule);
+        }
+        RuleCondition::Other { kind, payload } => match kind.as_str() {
+            "DOMAIN-REGEX" => ("domain_regex", json!([payload])),
+            "SRC-IP-CIDR" => ("source_ip_cidr", json!([payload])),
+            "DST-PORT" => ("port", json!([payload.parse::<u16>().ok()?])),
+            "SRC-PORT" => ("source_port", json!([payload.parse::<u16>().ok()?])),
+            "PROCESS-PATH" => ("process_path", json!([payload])),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut route_rule = Map::new();
+    route_rule.insert(key.to_string(), value);
+    Some(route_rule)
+}
+
+/// convert a clash rule into a sing-box route rule, `MATCH` rules are returned as `Err(outbound)`
+/// since they map to `route.final`
+fn rule_to_route_rule(rule: &Rule) -> Option<Result<JsonValue, String>> {
+    if rule.condition == RuleCondition::Match {
+        return Some(Err(rule.target.clone()));
+    }
+    let mut route_rule = condition_to_route_rule(&rule.condition)?;
+    route_rule.insert("outbound".to_string(), json!(rule.target));
+    Some(Ok(JsonValue::Object(route_rule)))
+}
+
+/// render the muxed clash config as a sing-box profile,
+/// proxies become outbounds, proxy groups become selector / urltest outbounds
+/// and rules become route rules
+pub fn to_singbox_config(
+    config: &ClashConfig,
+    diagnostics: &mut Vec<ConfigError>,
+) -> Result<JsonValue, ConfigError> {
+    let mut outbounds = vec![];
+    let mut tags = HashSet::<&str>::from([DIRECT_TAG, REJECT_TAG]);
+
+    let mut proxy_outbounds = vec![];
+    for proxy in &config.proxies {
+        if let Some(outbound) = proxy_to_outbound(proxy) {
+            tags.insert(proxy.name());
+            proxy_outbounds.push(outbound);
+        } else {
+            diagnostics.push(ConfigError::ProxyUnsupported {
+                proxy_name: proxy.name().to_string(),
+                proxy_type: proxy_type(proxy),
+                target: TARGET.name().to_string(),
+            });
+        }
+    }
+    let proxy_groups = config
+        .proxy_groups
+        .iter()
+        .filter(|g| {
+            let supported = !matches!(&g.kind, ProxyGroupKind::Other(kind) if kind == "relay");
+            if !supported {
+                diagnostics.push(ConfigError::ProxyGroupUnsupported {
+                    group_name: g.name.clone(),
+                    group_type: g.kind.name().to_string(),
+                    target: TARGET.name().to_string(),
+                });
+            }
+            supported
+        })
+        .collect::<Vec<_>>();
+    tags.extend(proxy_groups.iter().map(|g| g.name.as_str()));
+
+    for group in proxy_groups {
+        let members = group
+            .proxies
+            .iter()
+            .filter(|p| tags.contains(p.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut outbound = Map::new();
+        outbound.insert("tag".to_string(), json!(group.name));
+        outbound.insert("outbounds".to_string(), json!(members));
+        match &group.kind {
+            ProxyGroupKind::Select => {
+                outbound.insert("type".to_string(), json!("selector"));
+            }
+            // sing-box has no fallback or load balance outbound, url test is the closest
+            ProxyGroupKind::UrlTest
+            | ProxyGroupKind::Fallback
+            | ProxyGroupKind::LoadBalance
+            | ProxyGroupKind::Other(_) => {
+                outbound.insert("type".to_string(), json!("urltest"));
+                let others = serde_yaml::to_value(&group.others)?;
+                let others = others.as_mapping().cloned().unwrap_or_default();
+                outbound.insert(
+                    "url".to_string(),
+                    json!(get_str(&others, "url").unwrap_or(DEFAULT_URL_TEST_URL)),
+                );
+                insert_some(
+                    &mut outbound,
+                    "interval",
+                    get_u64(&others, "interval").map(|i| format!("{}s", i)),
+                );
+                insert_some(&mut outbound, "tolerance", get_u64(&others, "tolerance"));
+            }
+        }
+        outbounds.push(JsonValue::Object(outbound));
+    }
+
+    outbounds.extend(proxy_outbounds);
+    outbounds.push(json!({ "type": "direct", "tag": DIRECT_TAG }));
+    outbounds.push(json!({ "type": "block", "tag": REJECT_TAG }));
+
+    let mut route_rules = vec![];
+    let mut route_final = None;
+    for rule in &config.rules {
+        match rule_to_route_rule(rule) {
+            Some(Ok(route_rule)) => {
+                if route_rule
+                    .get("outbound")
+                    .and_then(JsonValue::as_str)
+                    .is_some_and(|o| tags.contains(o))
+                {
+                    route_rules.push(route_rule);
+                }
+            }
+            Some(Err(outbound)) => {
+                route_final.get_or_insert(outbound);
+            }
+            None => {
+                diagnostics.push(ConfigError::RuleUnsupported {
+                    rule: rule.to_string(),
+                    target: TARGET.name().to_string(),
+                });
+            }
+        }
+    }
+
+    let listen_port = config
+        .others
+        .get("mixed-port")
+        .or_else(|| config.others.get("port"))
+        .and_then(YamlValue::as_u64)
+        .unwrap_or(DEFAULT_MIXED_PORT);
+
+    let mut route = Map::new();
+    route.insert("rules".to_string(), json!(route_rules));
+    route.insert("auto_detect_interface".to_string(), json!(true));
+    insert_some(&mut route, "final", route_final);
+
+    Ok(json!({
+        "log": { "level": "info" },
+        "inbounds": [{
+            "type": "mixed",
+            "tag": "mixed-in",
+            "listen": "127.0.0.1",
+            "listen_port": listen_port,
+        }],
+        "outbounds": outbounds,
+        "route": route,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mux::{MuxSource, mux_configs};
+
+    #[test]
+    fn test_to_singbox_config() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let config2: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile2.yaml"))?;
+        let tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let sources = vec![
+            MuxSource::new("proxy1", config1),
+            MuxSource::new("proxy2", config2),
+        ];
+        let config = mux_configs("test", &tmpl, &sources, &[])?;
+
+        let mut diagnostics = vec![];
+        let profile = to_singbox_config(&config, &mut diagnostics)?;
+        let outbounds = profile["outbounds"].as_array().unwrap();
+
+        let proxy_group = outbounds.iter().find(|o| o["tag"] == "PROXY").unwrap();
+        assert_eq!(proxy_group["type"], "selector");
+        assert_eq!(
+            proxy_group["outbounds"],
+            json!(["SPEED", "QUANTITY", "DIRECT", "proxy1", "proxy2", "REJECT"])
+        );
+
+        let proxy = outbounds
+            .iter()
+            .find(|o| o["type"] == "shadowsocks")
+            .unwrap();
+        assert_eq!(proxy["method"], "aes-128-gcm");
+        assert!(proxy["server_port"].is_u64());
+
+        let rules = profile["route"]["rules"].as_array().unwrap();
+        assert!(
+            rules
+                .iter()
+                .any(|r| r["domain_suffix"] == json!(["proxy1.com"]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rule_to_route_rule() {
+        assert_eq!(
+            rule_to_route_rule(&"IP-CIDR,10.0.0.0/8,DIRECT,no-resolve".parse().unwrap()),
+            Some(Ok(
+                json!({ "ip_cidr": ["10.0.0.0/8"], "outbound": "DIRECT" })
+            ))
+        );
+        assert_eq!(
+            rule_to_route_rule(&"MATCH,PROXY".parse().unwrap()),
+            Some(Err("PROXY".to_string()))
+        );
+        assert_eq!(
+            rule_to_route_rule(&"GEOSITE,cn,DIRECT".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            rule_to_route_rule(
+                &"AND,((NOT,((DOMAIN,a.com))),(DST-PORT,443)),PROXY"
+                    .parse()
+                    .unwrap()
+            ),
+            Some(Ok(json!({
+                "type": "logical",
+                "mode": "and",
+                "rules": [{ "domain": ["a.com"], "invert": true }, { "port": [443] }],
+                "outbound": "PROXY"
+            })))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use super::fields::proxy_type;
+use crate::clash::{ClashConfig, ProxyGroupKind};
+use crate::error::ConfigError;
+use crate::models::profile::ProfileTarget;
+use std::collections::HashSet;
+
+const TARGET: ProfileTarget = ProfileTarget::Stash;
+const SUPPORTED_PROXY_TYPES: [&str; 12] = [
+    "ss",
+    "ssr",
+    "vmess",
+    "vless",
+    "trojan",
+    "hysteria",
+    "hysteria2",
+    "tuic",
+    "wireguard",
+    "socks5",
+    "http",
+    "snell",
+];
+const UNSUPPORTED_RULE_KINDS: [&str; 8] = [
+    "SUB-RULE", "IN-PORT", "IN-TYPE", "IN-USER", "IN-NAME", "UID", "DSCP", "NETWORK",
+];
+/// mihomo only top-level keys which stash refuses or ignores
+const UNSUPPORTED_TOP_LEVEL_KEYS: [&str; 6] = [
+    "tun",
+    "sniffer",
+    "profile",
+    "geodata-mode",
+    "geox-url",
+    "find-process-mode",
+];
+
+/// render the muxed clash config as a stash profile, which is clash yaml
+/// without the proxies, groups, rules and options stash does not understand
+pub fn to_stash_config(
+    config: &ClashConfig,
+    diagnostics: &mut Vec<ConfigError>,
+) -> Result<String, ConfigError> {
+    let mut dropped = HashSet::<String>::new();
+    let mut stash_config = config.clone();
+
+    stash_config
+        .others
+        .retain(|k, _| !UNSUPPORTED_TOP_LEVEL_KEYS.contains(&k.as_str()));
+
+    stash_config.proxies.retain(|proxy| {
+        let kind = proxy_type(proxy);
+        let supported = SUPPORTED_PROXY_TYPES.contains(&kind.as_str());
+        if !supported {
+            dropped.insert(proxy.name().to_string());
+            diagnostics.push(ConfigError::ProxyUnsupported {
+                proxy_name: proxy.name().to_string(),
+                proxy_type: kind,
+                target: TARGET.name().to_string(),
+            });
+        }
+        supported
+    });
+
+    stash_config.proxy_groups.retain(|group| {
+        let supported = !matches!(&group.kind, ProxyGroupKind::Other(kind) if kind == "relay");
+        if !supported {
+            dropped.insert(group.name.clone());
+            diagnostics.push(ConfigError::ProxyGroupUnsupported {
+                group_name: group.name.clone(),
+                group_type: group.kind.name().to_string(),
+                target: TARGET.name().to_string(),
+            });
+        }
+        supported
+    });
+    for group in &mut stash_config.proxy_groups {
+        group.proxies.retain(|p| !dropped.contains(p));
+    }
+
+    stash_config.rules.retain(|rule| {
+        let supported = !UNSUPPORTED_RULE_KINDS.contains(&rule.condition.kind())
+            && !dropped.contains(&rule.target);
+        if !supported {
+            diagnostics.push(ConfigError::RuleUnsupported {
+                rule: rule.to_string(),
+                target: TARGET.name().to_string(),
+            });
+        }
+        supported
+    });
+
+    Ok(serde_yaml::to_string(&stash_config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clash::ProxyGroup;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_to_stash_config() -> anyhow::Result<()> {
+        let mut config: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        config.proxies.push(serde_yaml::from_str(
+            "{ name: M, type: mieru, server: m.example.com, port: 443 }",
+        )?);
+        config.proxy_groups.push(ProxyGroup {
+            name: "Chain".to_string(),
+            kind: ProxyGroupKind::Other("relay".to_string()),
+            others: HashMap::new(),
+            proxies: vec!["A".to_string(), "M".to_string()],
+        });
+        config.proxy_groups[0].proxies.push("Chain".to_string());
+        config.rules.push("IN-PORT,7890,Chain".parse()?);
+
+        let mut diagnostics = vec![];
+        let profile = to_stash_config(&config, &mut diagnostics)?;
+        let stash_config: ClashConfig = serde_yaml::from_str(&profile)?;
+
+        assert_eq!(stash_config.proxies.len(), 2usize);
+        assert_eq!(stash_config.proxy_groups.len(), 1usize);
+        assert_eq!(
+            stash_config.proxy_groups[0].proxies,
+            vec!["Auto", "DIRECT", "A", "B"]
+        );
+        assert_eq!(stash_config.rules.len(), 1usize);
+        assert!(!stash_config.others.contains_key("profile"));
+        assert_eq!(diagnostics.len(), 3usize);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+use super::fields::{get_bool, get_mapping, get_mbps, get_str, get_u64, proxy_fields, proxy_type};
+use super::{check_line_name, drop_emptied_groups};
+use crate::clash::{ClashConfig, Proxy, ProxyGroupKind, Rule};
+use crate::error::ConfigError;
+use crate::models::profile::ProfileTarget;
+use std::collections::HashSet;
+
+const TARGET: ProfileTarget = ProfileTarget::Surge;
+const SUPPORTED_RULE_KINDS: [&str; 14] = [
+    "DOMAIN",
+    "DOMAIN-SUFFIX",
+    "DOMAIN-KEYWORD",
+    "IP-CIDR",
+    "IP-CIDR6",
+    "GEOIP",
+    "IP-ASN",
+    "PROCESS-NAME",
+    "SRC-IP",
+    "DEST-PORT",
+    "IN-PORT",
+    "AND",
+    "OR",
+    "NOT",
+];
+
+fn tls_params(params: &mut Vec<String>, fields: &serde_yaml::Mapping, server_name_key: &str) {
+    if let Some(sni) = get_str(fields, server_name_key).or_else(|| get_str(fields, "sni")) {
+        params.push(format!("sni={}", sni));
+    }
+    if get_bool(fields, "skip-cert-verify") {
+        params.push(String::from("skip-cert-verify=true"));
+    }
+}
+
+/// websocket is the only clash transport surge understands
+fn ws_params(params: &mut Vec<String>, fields: &serde_yaml::Mapping) -> Option<()> {
+    match get_str(fields, "network") {
+        None | Some("tcp") => {}
+        Some("ws") => {
+            params.push(String::from("ws=true"));
+            if let Some(ws_opts) = get_mapping(fields, "ws-opts") {
+                if let Some(path) = get_str(ws_opts, "path") {
+                    params.push(format!("ws-path={}", path));
+                }
+                if let Some(host) = get_mapping(ws_opts, "headers").and_then(|h| get_str(h, "Host"))
+                {
+                    params.push(format!("ws-headers=Host:{}", host));
+                }
+            }
+        }
+        Some(_) => return None,
+    }
+    Some(())
+}
+
+/// convert a clash proxy into a surge `[Proxy]` line, returns `None` if the proxy is unsupported
+pub fn proxy_to_line(proxy: &Proxy) -> Option<String> {
+    let fields = &proxy_fields(proxy)?;
+    let port = get_u64(fields, "port")?;
+    let mut params = vec![];
+
+    let kind = match get_str(fields, "type")? {
+        "ss" => {
+            params.push(format!("encrypt-method={}", get_str(fields, "cipher")?));
+            params.push(format!("password={}", get_str(fields, "password")?));
+            match get_str(fields, "plugin") {
+                None => {}
+                Some("obfs") => {
+                    let plugin_opts = get_mapping(fields, "plugin-opts")?;
+                    params.push(format!("obfs={}", get_str(plugin_opts, "mode")?));
+                    if let Some(host) = get_str(plugin_opts, "host") {
+                        params.push(format!("obfs-host={}", host));
+                    }
+                }
+                Some(_) => return None,
+            }
+            if get_bool(fields, "udp") {
+                params.push(String::from("udp-relay=true"));
+            }
+            "ss"
+        }
+        "vmess" => {
+            params.push(format!("username={}", get_str(fields, "uuid")?));
+            if get_u64(fields, "alterId").unwrap_or_default() == 0 {
+                params.push(String::from("vmess-aead=true"));
+            }
+            if get_bool(fields, "tls") {
+                params.push(String::from("tls=true"));
+                tls_params(&mut params, fields, "servername");
+            }
+            ws_params(&mut params, fields)?;
+            "vmess"
+        }
+        "trojan" => {
+            params.push(format!("password={}", get_str(fields, "password")?));
+            tls_params(&mut params, fields, "sni");
+            ws_params(&mut params, fields)?;
+            "trojan"
+        }
+        "hysteria2" => {
+            params.push(format!("password={}", get_str(fields, "password")?));
+            if let Some(down) = get_mbps(fields, "down") {
+                params.push(format!("download-bandwidth={}", down));
+            }
+            tls_params(&mut params, fields, "sni");
+            "hysteria2"
+        }
+        "tuic" => {
+            params.push(format!("uuid={}", get_str(fields, "uuid")?));
+            params.push(format!("password={}", get_str(fields, "password")?));
+            tls_params(&mut params, fields, "sni");
+            "tuic-v5"
+        }
+        "snell" => {
+            params.push(format!("psk={}", get_str(fields, "psk")?));
+            if let Some(version) = get_u64(fields, "version") {
+                params.push(format!("version={}", version));
+            }
+            if let Some(obfs_opts) = get_mapping(fields, "obfs-opts") {
+                params.push(format!("obfs={}", get_str(obfs_opts, "mode")?));
+                if let Some(host) = get_str(obfs_opts, "host") {
+                    params.push(format!("obfs-host={}", host));
+                }
+            }
+            "snell"
+        }
+        kind @ ("socks5" | "http") => {
+            if let (Some(username), Some(password)) =
+                (get_str(fields, "username"), get_str(fields, "password"))
+            {
+                params.push(username.to_string());
+                params.push(password.to_string());
+            }
+            let tls = get_bool(fields, "tls");
+            if tls {
+                tls_params(&mut params, fields, "sni");
+            }
+            match (kind, tls) {
+                ("socks5", false) => "socks5",
+                ("socks5", true) => "socks5-tls",
+                (_, false) => "http",
+                (_, true) => "https",
+            }
+        }
+        _ => return None,
+    };
+
+    let mut line = format!("{} = {}, {}, {}", proxy.name(), kind, proxy.server(), port);
+    for param in params {
+        line.push_str(", ");
+        line.push_str(&param);
+    }
+    Some(line)
+}
+
+/// convert a clash rule into a surge `[Rule]` line, returns `None` if the rule is unsupported
+pub fn rule_to_line(rule: &Rule) -> Option<String> {
+    let kind = match rule.condition.kind() {
+        "MATCH" => return Some(format!("FINAL,{}", rule.target)),
+        "DST-PORT" => "DEST-PORT",
+        "SRC-IP-CIDR" => "SRC-IP",
+        kind if SUPPORTED_RULE_KINDS.contains(&kind) => kind,
+        _ => return None,
+    };
+    let mut line = format!("{},{},{}", kind, rule.condition.payload(), rule.target);
+    if rule.no_resolve {
+        line.push_str(",no-resolve");
+    }
+    Some(line)
+}
+
+/// render the muxed clash config as a surge profile
+pub fn to_surge_config(
+    config: &ClashConfig,
+    diagnostics: &mut Vec<ConfigError>,
+) -> Result<String, ConfigError> {
+    let mut dropped = HashSet::<&str>::new();
+    let mut proxy_lines = vec![];
+    for proxy in &config.proxies {
+        if !check_line_name(proxy.name(), &TARGET, diagnostics) {
+            dropped.insert(proxy.name());
+        } else if let Some(line) = proxy_to_line(proxy) {
+            proxy_lines.push(line);
+        } else {
+            dropped.insert(proxy.name());
+            diagnostics.push(ConfigError::ProxyUnsupported {
+                proxy_name: proxy.name().to_string(),
+                proxy_type: proxy_type(proxy),
+                target: TARGET.name().to_string(),
+            });
+        }
+    }
+
+    let mut group_lines = vec![];
+    let groups = config
+        .proxy_groups
+        .iter()
+        .filter(|g| {
+            if !check_line_name(&g.name, &TARGET, diagnostics) {
+                dropped.insert(&g.name);
+                return false;
+            }
+            let supported = !matches!(&g.kind, ProxyGroupKind::Other(_));
+            if !supported {
+                dropped.insert(&g.name);
+                diagnostics.push(ConfigError::ProxyGroupUnsupported {
+                    group_name: g.name.clone(),
+                    group_type: g.kind.name().to_string(),
+                    target: TARGET.name().to_string(),
+                });
+            }
+            supported
+        })
+        .collect::<Vec<_>>();
+    drop_emptied_groups(&groups, &mut dropped, &TARGET, diagnostics);
+
+    for group in groups
+        .into_iter()
+        .filter(|g| !dropped.contains(g.name.as_str()))
+    {
+        let mut line = format!("{} = {}", group.name, group.kind.name());
+        for member in group
+            .proxies
+            .iter()
+            .filter(|p| !dropped.contains(p.as_str()))
+        {
+            line.push_str(", ");
+            line.push_str(member);
+        }
+        for key in ["url", "interval", "tolerance"] {
+            if let Some(value) = group.others.get(key) {
+                let value = match value {
+                    serde_yaml::Value::String(s) => s.clone(),
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    _ => continue,
+                };
+                line.push_str(&format!(", {}={}", key, value));
+            }
+        }
+        group_lines.push(line);
+    }
+
+    let mut rule_lines = vec![];
+    for rule in &config.rules {
+        let line = match dropped.contains(rule.target.as_str()) {
+            true => None,
+            false => rule_to_line(rule),
+        };
+        match line {
+            Some(line) => rule_lines.push(line),
+            None => diagnostics.push(ConfigError::RuleUnsupported {
+                rule: rule.to_string(),
+                target: TARGET.name().to_string(),
+            }),
+        }
+    }
+
+    let general_lines = vec![
+        String::from("loglevel = notify"),
+        String::from(
+            "skip-proxy = 127.0.0.1, 192.168.0.0/16, 10.0.0.0/8, 172.16.0.0/12, localhost, *.local",
+        ),
+    ];
+
+    let mut content = String::new();
+    for (section, lines) in [
+        ("General", general_lines),
+        ("Proxy", proxy_lines),
+        ("Proxy Group", group_lines),
+        ("Rule", rule_lines),
+    ] {
+        content.push_str(&format!("[{}]\n", section));
+        for line in lines {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clash::RuleCondition;
+    use crate::mux::{MuxSource, mux_configs};
+
+    #[test]
+    fn test_to_surge_config() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let config2: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile2.yaml"))?;
+        let tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let sources = vec![
+            MuxSource::new("proxy1", config1),
+            MuxSource::new("proxy2", config2),
+        ];
+        let mut config = mux_configs("test", &tmpl, &sources, &[])?;
+        config.proxies.push(serde_yaml::from_str(
+            "{ name: V, type: vless, server: v.example.com, port: 443, uuid: x }",
+        )?);
+        config.rules.push(Rule::new(
+            RuleCondition::GeoSite("cn".to_string()),
+            "DIRECT",
+        ));
+        config.rules.push(Rule::new(RuleCondition::Match, "PROXY"));
+
+        let mut diagnostics = vec![];
+        let profile = to_surge_config(&config, &mut diagnostics)?;
+
+        assert!(
+            profile.contains("PROXY = select, SPEED, QUANTITY, DIRECT, proxy1, proxy2, REJECT")
+        );
+        assert!(profile.contains("encrypt-method=aes-128-gcm"));
+        assert!(profile.contains("DOMAIN-SUFFIX,proxy1.com,DIRECT"));
+        assert!(profile.contains("FINAL,PROXY"));
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::ProxyUnsupported { proxy_name, .. } if proxy_name == "V"
+        )));
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::RuleUnsupported { rule, .. } if rule == "GEOSITE,cn,DIRECT"
+        )));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_surge_config_emptied_groups() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: V, type: vless, server: v.example.com, port: 443, uuid: x }
+  - { name: "A, B", type: socks5, server: a.example.com, port: 1 }
+  - { name: S, type: socks5, server: s.example.com, port: 1 }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [ONLY-V, S] }
+  - { name: ONLY-V, type: select, proxies: [V, "A, B"] }
+  - { name: OUTER, type: select, proxies: [ONLY-V] }
+  - { name: "X=Y", type: select, proxies: [S] }
+rules:
+  - MATCH,OUTER
+            "#,
+        )?;
+
+        let mut diagnostics = vec![];
+        let profile = to_surge_config(&config, &mut diagnostics)?;
+
+        assert!(profile.contains("PROXY = select, S\n"));
+        assert!(!profile.contains("ONLY-V"));
+        assert!(!profile.contains("OUTER"));
+        assert!(!profile.contains("A, B"));
+        assert!(!profile.contains("X=Y"));
+        for name in ["ONLY-V", "OUTER"] {
+            assert!(diagnostics.iter().any(|d| matches!(
+                d,
+                ConfigError::ProxyGroupUnsupported { group_name, .. } if group_name == name
+            )));
+        }
+        for name in ["A, B", "X=Y"] {
+            assert!(diagnostics.iter().any(|d| matches!(
+                d,
+                ConfigError::NameUnsupported { name: n, .. } if n == name
+            )));
+        }
+        assert!(diagnostics.iter().any(|d| matches!(
+            d,
+            ConfigError::RuleUnsupported { rule, .. } if rule == "MATCH,OUTER"
+        )));
+
+        Ok(())
+    }
+}
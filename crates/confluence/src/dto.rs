@@ -1,7 +1,39 @@
+use crate::crypto::{MASK, SecretCipher, mask_url};
+use crate::fetch::ProxySettings;
 use crate::models;
+use crate::models::confluence::{MuxFailure, SourceAutoGroup};
+use crate::models::confluence_revision::{DiffStats, MuxDiff, RevisionSource};
+use crate::models::profile::ProfileTarget;
+use crate::models::subscribe_source::{
+    FetchOverrides, ProxyMatcher, ProxyRenameRules, RequestHeaders,
+};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+/// tell an absent field (`None`) apart from an explicit `null` (`Some(None)`)
+mod double_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
 #[ts(export)]
 pub struct ProfileDto {
@@ -12,6 +44,9 @@ pub struct ProfileDto {
     #[ts(type = "number")]
     pub updated_at: i64,
     pub resource_token: String,
+    pub target: ProfileTarget,
+    #[ts(optional)]
+    pub revision_id: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
@@ -37,13 +72,91 @@ pub struct SubscribeSourceDto {
     pub passive_sync: Option<bool>,
     pub proxy_server: Option<String>,
     pub proxy_auth: Option<String>,
+    /// `proxy_server` and `proxy_auth` read back, if they can be
+    #[ts(optional)]
+    pub proxy: Option<ProxySettings>,
+    pub import_proxy_groups: Option<bool>,
+    pub proxy_include: Option<ProxyMatcher>,
+    pub proxy_exclude: Option<ProxyMatcher>,
+    pub proxy_rename: Option<ProxyRenameRules>,
+    pub tags: Vec<String>,
+    #[ts(optional)]
+    pub pinned_snapshot_id: Option<i32>,
+    #[ts(type = "number", optional)]
+    pub last_sync_at: Option<i64>,
+    #[ts(optional)]
+    pub last_sync_status: Option<i32>,
+    #[ts(optional)]
+    pub last_error: Option<String>,
+    pub consecutive_failures: i32,
+    #[ts(optional)]
+    pub fetch_overrides: Option<FetchOverrides>,
+    /// values are masked unless revealed
+    #[ts(optional)]
+    pub request_headers: Option<RequestHeaders>,
 }
 
-impl From<models::subscribe_source::Model> for SubscribeSourceDto {
-    fn from(value: models::subscribe_source::Model) -> Self {
+impl SubscribeSourceDto {
+    /// the url and proxy settings are masked unless revealed, and always masked if they
+    /// can not be opened
+    pub fn from_orm(
+        value: models::subscribe_source::Model,
+        cipher: &SecretCipher,
+        reveal: bool,
+    ) -> Self {
+        let open = |v: &str| cipher.open(v).ok();
+        let url = open(&value.url);
+        let proxy_server = value.proxy_server.as_deref().map(open);
+        let proxy_auth = value.proxy_auth.as_deref().map(open);
+        let proxy = proxy_server
+            .clone()
+            .flatten()
+            .filter(|p| !p.is_empty())
+            .and_then(|p| {
+                ProxySettings::from_columns(&p, proxy_auth.clone().flatten().as_deref()).ok()
+            });
+        let request_headers = value.request_headers.map(|headers| {
+            RequestHeaders(
+                headers
+                    .0
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let value = open(&value)
+                            .filter(|_| reveal)
+                            .unwrap_or_else(|| MASK.to_string());
+                        (name, value)
+                    })
+                    .collect(),
+            )
+        });
+        let (url, proxy_server, proxy_auth, proxy) = if reveal {
+            (
+                url.unwrap_or_else(|| MASK.to_string()),
+                proxy_server.map(|p| p.unwrap_or_else(|| MASK.to_string())),
+                proxy_auth.map(|a| a.unwrap_or_else(|| MASK.to_string())),
+                proxy,
+            )
+        } else {
+            (
+                url.map_or_else(|| MASK.to_string(), |u| mask_url(&u)),
+                proxy_server.map(|p| match (p, &proxy) {
+                    (Some(p), _) if p.is_empty() => p,
+                    (_, Some(proxy)) => proxy.server_url(),
+                    _ => MASK.to_string(),
+                }),
+                proxy_auth.map(|a| match a {
+                    Some(a) if a.is_empty() => a,
+                    _ => MASK.to_string(),
+                }),
+                proxy.map(|p| ProxySettings {
+                    password: p.password.map(|_| MASK.to_string()),
+                    ..p
+                }),
+            )
+        };
         Self {
             id: value.id,
-            url: value.url,
+            url,
             confluence_id: value.confluence_id,
             created_at: value.created_at.and_utc().timestamp_millis(),
             updated_at: value.updated_at.and_utc().timestamp_millis(),
@@ -54,8 +167,21 @@ impl From<models::subscribe_source::Model> for SubscribeSourceDto {
             sub_total: value.sub_total,
             sub_upload: value.sub_upload,
             passive_sync: value.passive_sync,
-            proxy_auth: value.proxy_auth,
-            proxy_server: value.proxy_server,
+            proxy,
+            proxy_auth,
+            proxy_server,
+            import_proxy_groups: value.import_proxy_groups,
+            proxy_include: value.proxy_include,
+            proxy_exclude: value.proxy_exclude,
+            proxy_rename: value.proxy_rename,
+            tags: value.tags,
+            pinned_snapshot_id: value.pinned_snapshot_id,
+            last_sync_at: value.last_sync_at.map(|s| s.and_utc().timestamp_millis()),
+            last_sync_status: value.last_sync_status,
+            last_error: value.last_error,
+            consecutive_failures: value.consecutive_failures,
+            fetch_overrides: value.fetch_overrides,
+            request_headers,
         }
     }
 }
@@ -68,6 +194,8 @@ impl From<models::profile::Model> for ProfileDto {
             created_at: value.created_at.and_utc().timestamp_millis(),
             updated_at: value.updated_at.and_utc().timestamp_millis(),
             resource_token: value.resource_token,
+            target: value.target,
+            revision_id: value.revision_id,
         }
     }
 }
@@ -105,6 +233,16 @@ pub struct ConfluenceDto {
     #[ts(type = "number", optional)]
     pub cron_next_at: Option<i64>,
     pub user_agent: String,
+    #[ts(optional)]
+    pub source_auto_group: Option<SourceAutoGroup>,
+    #[ts(optional)]
+    pub template_id: Option<i32>,
+    pub degraded_mux: bool,
+    #[ts(optional)]
+    pub mux_failures: Option<Vec<MuxFailure>>,
+    /// only returned by a sync of the confluence
+    #[ts(optional)]
+    pub sync_results: Option<Vec<SubscribeSourceSyncResultDto>>,
 }
 
 impl ConfluenceDto {
@@ -112,6 +250,7 @@ impl ConfluenceDto {
         confluence: models::confluence::Model,
         sms: Vec<models::subscribe_source::Model>,
         pms: Vec<models::profile::Model>,
+        cipher: &SecretCipher,
     ) -> Self {
         Self {
             id: confluence.id,
@@ -120,7 +259,10 @@ impl ConfluenceDto {
             updated_at: confluence.updated_at.and_utc().timestamp_millis(),
             creator: confluence.creator,
             mux_content: confluence.mux_content,
-            subscribe_sources: sms.into_iter().map(|s| s.into()).collect(),
+            subscribe_sources: sms
+                .into_iter()
+                .map(|s| SubscribeSourceDto::from_orm(s, cipher, false))
+                .collect(),
             profiles: pms.into_iter().map(|s| s.into()).collect(),
             name: confluence.name,
             sub_download: confluence.sub_download,
@@ -139,14 +281,69 @@ impl ConfluenceDto {
                 .cron_next_at
                 .map(|s| s.and_utc().timestamp_millis()),
             user_agent: confluence.user_agent,
+            source_auto_group: confluence.source_auto_group,
+            template_id: confluence.template_id,
+            degraded_mux: confluence.degraded_mux,
+            mux_failures: confluence.mux_failures.map(|f| f.0),
+            sync_results: None,
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum SubscribeSourceSyncStatus {
+    Synced,
+    /// the source answered not modified
+    Unchanged,
+    /// passive sources are only synced on their own
+    Skipped,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct SubscribeSourceSyncResultDto {
+    pub subscribe_source_id: i32,
+    pub name: String,
+    pub status: SubscribeSourceSyncStatus,
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
 #[ts(export)]
 pub struct ProfileCreationDto {
     pub confluence_id: i32,
+    #[ts(optional)]
+    pub target: Option<ProfileTarget>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProfileUpdateDto {
+    #[ts(optional)]
+    pub target: Option<ProfileTarget>,
+    /// pin the profile to a revision of its confluence, `null` follows the latest mux
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<i32>")]
+    pub revision_id: Option<Option<i32>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProfileTokenQueryDto {
+    #[ts(optional)]
+    pub target: Option<ProfileTarget>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct RevisionDiffQueryDto {
+    /// the revision to compare with, the previous revision by default
+    #[ts(optional)]
+    pub against: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
@@ -158,6 +355,39 @@ pub struct SubscribeSourceCreationDto {
     pub passive_sync: Option<bool>,
     pub proxy_server: Option<String>,
     pub proxy_auth: Option<String>,
+    /// replaces `proxy_server` and `proxy_auth` if given
+    #[ts(optional)]
+    pub proxy: Option<ProxySettings>,
+    pub import_proxy_groups: Option<bool>,
+    pub proxy_include: Option<ProxyMatcher>,
+    pub proxy_exclude: Option<ProxyMatcher>,
+    pub proxy_rename: Option<ProxyRenameRules>,
+    pub tags: Option<Vec<String>>,
+    pub fetch_overrides: Option<FetchOverrides>,
+    #[ts(optional)]
+    pub request_headers: Option<RequestHeaders>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct SubscribeSourceQueryDto {
+    /// show the url and proxy credentials instead of masking them
+    #[ts(optional)]
+    pub reveal: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProxyTestDto {
+    pub proxy: ProxySettings,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProxyTestResultDto {
+    pub reachable: bool,
+    #[ts(type = "number")]
+    pub latency_ms: i64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
@@ -169,6 +399,35 @@ pub struct SubscribeSourceUpdateDto {
     pub passive_sync: Option<bool>,
     pub proxy_server: Option<String>,
     pub proxy_auth: Option<String>,
+    /// replaces `proxy_server` and `proxy_auth` if given, `null` fetches without proxy
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<ProxySettings>")]
+    pub proxy: Option<Option<ProxySettings>>,
+    pub import_proxy_groups: Option<bool>,
+    /// `null` keeps every proxy
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<ProxyMatcher>")]
+    pub proxy_include: Option<Option<ProxyMatcher>>,
+    /// `null` drops no proxy
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<ProxyMatcher>")]
+    pub proxy_exclude: Option<Option<ProxyMatcher>>,
+    /// `null` keeps the proxy names of the source
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<ProxyRenameRules>")]
+    pub proxy_rename: Option<Option<ProxyRenameRules>>,
+    pub tags: Option<Vec<String>>,
+    /// mux an earlier snapshot, `null` goes back to the latest content
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<i32>")]
+    pub pinned_snapshot_id: Option<Option<i32>>,
+    /// `null` goes back to the server defaults
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<FetchOverrides>")]
+    pub fetch_overrides: Option<Option<FetchOverrides>>,
+    /// replaces every header, masked values keep the stored value of the same header
+    #[ts(optional)]
+    pub request_headers: Option<RequestHeaders>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
@@ -180,6 +439,16 @@ pub struct ConfluenceUpdateDto {
     pub user_agent: Option<String>,
     #[ts(optional)]
     pub name: Option<String>,
+    /// `null` disables the generated source groups
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<SourceAutoGroup>")]
+    pub source_auto_group: Option<Option<SourceAutoGroup>>,
+    /// follow a library template, `null` goes back to the inline template
+    #[serde(default, with = "double_option")]
+    #[ts(optional = nullable, as = "Option<i32>")]
+    pub template_id: Option<Option<i32>>,
+    #[ts(optional)]
+    pub degraded_mux: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
@@ -188,3 +457,148 @@ pub struct ConfluenceUpdateCronDto {
     pub cron_expr: String,
     pub cron_expr_tz: String,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct TemplateDto {
+    pub id: i32,
+    pub name: String,
+    pub creator: String,
+    pub content: String,
+    pub shared: bool,
+    pub version: i32,
+    #[ts(type = "number")]
+    pub created_at: i64,
+    #[ts(type = "number")]
+    pub updated_at: i64,
+}
+
+impl From<models::template::Model> for TemplateDto {
+    fn from(value: models::template::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            creator: value.creator,
+            content: value.content,
+            shared: value.shared,
+            version: value.version,
+            created_at: value.created_at.and_utc().timestamp_millis(),
+            updated_at: value.updated_at.and_utc().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct TemplateVersionDto {
+    pub id: i32,
+    pub template_id: i32,
+    pub version: i32,
+    pub content: String,
+    #[ts(type = "number")]
+    pub created_at: i64,
+}
+
+impl From<models::template_version::Model> for TemplateVersionDto {
+    fn from(value: models::template_version::Model) -> Self {
+        Self {
+            id: value.id,
+            template_id: value.template_id,
+            version: value.version,
+            content: value.content,
+            created_at: value.created_at.and_utc().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct TemplateCreationDto {
+    pub name: String,
+    pub content: String,
+    #[ts(optional)]
+    pub shared: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct TemplateUpdateDto {
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// a changed content creates a new version and re-muxes the following confluences
+    #[ts(optional)]
+    pub content: Option<String>,
+    #[ts(optional)]
+    pub shared: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ConfluenceRevisionDto {
+    pub id: i32,
+    pub confluence_id: i32,
+    /// only returned when a single revision is requested
+    #[ts(optional)]
+    pub content: Option<String>,
+    pub content_hash: String,
+    pub sources: Vec<RevisionSource>,
+    #[ts(optional)]
+    pub diff_stats: Option<DiffStats>,
+    /// only returned when a single revision is requested
+    #[ts(optional)]
+    pub diff: Option<MuxDiff>,
+    #[ts(type = "number")]
+    pub created_at: i64,
+}
+
+impl ConfluenceRevisionDto {
+    pub fn from_orm(value: models::confluence_revision::Model, with_content: bool) -> Self {
+        Self {
+            id: value.id,
+            confluence_id: value.confluence_id,
+            content: with_content.then_some(value.content),
+            content_hash: value.content_hash,
+            sources: value.sources.0,
+            diff_stats: value.diff_stats,
+            diff: value.diff.filter(|_| with_content),
+            created_at: value.created_at.and_utc().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct SubscribeSourceSnapshotDto {
+    pub id: i32,
+    pub subscribe_source_id: i32,
+    /// only returned when a single snapshot is requested
+    #[ts(optional)]
+    pub content: Option<String>,
+    pub status: i32,
+    #[ts(type = "number", optional)]
+    pub sub_upload: Option<i64>,
+    #[ts(type = "number", optional)]
+    pub sub_download: Option<i64>,
+    #[ts(type = "number", optional)]
+    pub sub_total: Option<i64>,
+    #[ts(type = "number", optional)]
+    pub sub_expire: Option<i64>,
+    #[ts(type = "number")]
+    pub fetched_at: i64,
+}
+
+impl SubscribeSourceSnapshotDto {
+    pub fn from_orm(value: models::subscribe_source_snapshot::Model, with_content: bool) -> Self {
+        Self {
+            id: value.id,
+            subscribe_source_id: value.subscribe_source_id,
+            content: with_content.then_some(value.content),
+            status: value.status,
+            sub_upload: value.sub_upload,
+            sub_download: value.sub_download,
+            sub_total: value.sub_total,
+            sub_expire: value.sub_expire.map(|s| s.and_utc().timestamp_millis()),
+            fetched_at: value.fetched_at.and_utc().timestamp_millis(),
+        }
+    }
+}
@@ -1,7 +1,9 @@
+use crate::mux::validate::ValidationReport;
+use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::Json;
 use reqwest::Error as FetchError;
+use reqwest::header::HeaderValue;
 use sea_orm::DbErr;
 use std::fmt::Debug;
 use std::net::AddrParseError;
@@ -9,7 +11,9 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
-    #[error("invalid server format {server} from source config {config_name}, caused by {source_kind:?}")]
+    #[error(
+        "invalid server format {server} from source config {config_name}, caused by {source_kind:?}"
+    )]
     ProxyServerInvalid {
         config_name: String,
         server: String,
@@ -25,12 +29,109 @@ pub enum ConfigError {
     },
     #[error(transparent)]
     Format(#[from] serde_yaml::Error),
+    #[error("invalid share link {link} from source config {config_name}, caused by {reason}")]
+    ShareLinkInvalid {
+        config_name: String,
+        link: String,
+        reason: String,
+    },
+    #[error("proxy {proxy_name} of type {proxy_type} can not be expressed in {target} profile")]
+    ProxyUnsupported {
+        proxy_name: String,
+        proxy_type: String,
+        target: String,
+    },
+    #[error(
+        "proxy group {group_name} of type {group_type} can not be expressed in {target} profile"
+    )]
+    ProxyGroupUnsupported {
+        group_name: String,
+        group_type: String,
+        target: String,
+    },
+    #[error("name {name} can not be expressed in {target} profile, it contains , or =")]
+    NameUnsupported { name: String, target: String },
+    #[error("invalid rule {rule}, caused by {reason}")]
+    RuleInvalid { rule: String, reason: String },
+    #[error("rule {rule} can not be expressed in {target} profile")]
+    RuleUnsupported { rule: String, target: String },
+    #[error(
+        "invalid proxy filter pattern {pattern} of source config {config_name}, caused by {source}"
+    )]
+    ProxyFilterInvalid {
+        config_name: String,
+        pattern: String,
+        source: regex::Error,
+    },
+    #[error("invalid mux slot {slot}, caused by {reason}")]
+    MuxSlotInvalid { slot: String, reason: String },
+    #[error("invalid region {region}, caused by {reason}")]
+    RegionInvalid { region: String, reason: String },
+    #[error("muxed config {config_name} has broken references: {report}")]
+    MuxInvalid {
+        config_name: String,
+        report: ValidationReport,
+    },
+    #[error("invalid request header {name}, caused by {reason}")]
+    RequestHeaderInvalid { name: String, reason: String },
+    #[error("invalid upstream proxy {proxy}, caused by {reason}")]
+    UpstreamProxyInvalid { proxy: String, reason: String },
     #[error("subscribe source {subscribe_source_name} empty or not sync, please sync first")]
     NotSync { subscribe_source_name: String },
+    #[error("no subscribe source could be muxed, caused by {reasons}")]
+    SourcesUnavailable { reasons: String },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error(transparent)]
+    Request(#[from] FetchError),
+    #[error("subscription rejected with status {status}, the token may be invalid")]
+    Unauthorized { status: StatusCode },
+    #[error("subscription gone with status {status}, it may have expired")]
+    Expired { status: StatusCode },
+    #[error("subscription request failed with status {status}")]
+    ClientError { status: StatusCode },
+    #[error("subscription server failed with status {status}")]
+    ServerError { status: StatusCode },
+    #[error("unexpected status {status} of subscription")]
+    UnexpectedStatus { status: StatusCode },
+    #[error("unexpected content type {content_type:?} of subscription, likely an error page")]
+    UnexpectedContentType { content_type: HeaderValue },
+    #[error("subscription body of {size} bytes is too small, likely an error message")]
+    BodyTooSmall { size: usize },
+    #[error("response body exceeds {limit} bytes")]
+    BodyTooLarge { limit: usize },
+    #[error("invalid subscription content, caused by {source}")]
+    InvalidContent { source: ConfigError },
+}
+
+impl SyncError {
+    /// the http status of the response the error was raised for, if any
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Request(err) => err.status(),
+            Self::Unauthorized { status }
+            | Self::Expired { status }
+            | Self::ClientError { status }
+            | Self::ServerError { status }
+            | Self::UnexpectedStatus { status } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// a later attempt may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Request(err) => err.is_timeout(),
+            Self::ServerError { .. } => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error(transparent)]
@@ -45,8 +146,10 @@ pub enum AppError {
     Unauthorized(anyhow::Error),
     #[error("{message}")]
     BadRequest { message: String },
-    #[error("Invalid proxy auth header")]
-    InvalidProxyAuthHeader,
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+    #[error("secret can not be sealed or opened, caused by {reason}")]
+    Secret { reason: String },
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -78,7 +181,9 @@ impl IntoResponse for AppError {
             Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
             Self::Fetch(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
-            Self::InvalidProxyAuthHeader => StatusCode::BAD_REQUEST,
+            Self::Sync(SyncError::Request(err)) if err.is_timeout() => StatusCode::GATEWAY_TIMEOUT,
+            Self::Sync(_) => StatusCode::BAD_GATEWAY,
+            Self::Secret { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let error_msg = self.to_string();
         let error_body = serde_json::json!({ "error_msg": error_msg });
@@ -0,0 +1,213 @@
+use crate::config::FetchConfig;
+use crate::error::AppError;
+use crate::fetch::proxy::legacy_auth_header;
+use crate::fetch::{ProxyScheme, ProxySettings};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// clients kept at most, the least recently used one is dropped first
+const CLIENT_CACHE_CAPACITY: usize = 64;
+/// clients unused for longer are dropped, with their connection pools
+const CLIENT_CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// the connection settings a client is built for
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientKey {
+    pub user_agent: Option<String>,
+    pub proxy_server: Option<String>,
+    pub proxy_auth: Option<String>,
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+}
+
+impl ClientKey {
+    pub fn new(config: &FetchConfig) -> Self {
+        Self {
+            user_agent: None,
+            proxy_server: None,
+            proxy_auth: None,
+            connect_timeout: config.connect_timeout,
+            read_timeout: config.read_timeout,
+        }
+    }
+}
+
+struct CachedClient {
+    client: reqwest::Client,
+    last_used: Instant,
+}
+
+/// builds clients once per connection settings, so their connection pools and tls
+/// sessions outlive a single fetch
+pub struct Fetcher {
+    config: FetchConfig,
+    clients: Mutex<HashMap<ClientKey, CachedClient>>,
+    jwks_client: OnceLock<reqwest::Client>,
+}
+
+impl Fetcher {
+    pub fn new(config: FetchConfig) -> Self {
+        Self {
+            config,
+            clients: Mutex::new(HashMap::new()),
+            jwks_client: OnceLock::new(),
+        }
+    }
+
+    pub fn config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    /// a client for the identity provider, certificates are always verified whatever
+    /// `accept_invalid_certs` says, since the keys it fetches authenticate every request
+    pub fn jwks_client(&self) -> Result<reqwest::Client, AppError> {
+        if let Some(client) = self.jwks_client.get() {
+            return Ok(client.clone());
+        }
+        let mut client_builder = reqwest::ClientBuilder::new()
+            .connect_timeout(self.config.connect_timeout)
+            .read_timeout(self.config.read_timeout)
+            .timeout(self.config.total_timeout);
+        for cert in &self.config.ca_certificates {
+            client_builder = client_builder.add_root_certificate(cert.clone());
+        }
+        let client = client_builder.build()?;
+        Ok(self.jwks_client.get_or_init(|| client).clone())
+    }
+
+    pub fn client(&self, key: ClientKey) -> Result<reqwest::Client, AppError> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        clients.retain(|_, cached| now.duration_since(cached.last_used) < CLIENT_CACHE_TTL);
+        if let Some(cached) = clients.get_mut(&key) {
+            cached.last_used = now;
+            return Ok(cached.client.clone());
+        }
+        let client = self.build_client(&key)?;
+        if clients.len() >= CLIENT_CACHE_CAPACITY
+            && let Some(oldest) = clients
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone())
+        {
+            clients.remove(&oldest);
+        }
+        clients.insert(
+            key,
+            CachedClient {
+                client: client.clone(),
+                last_used: now,
+            },
+        );
+        Ok(client)
+    }
+
+    /// a client left out of the cache, for settings which may never be used again
+    pub fn build_client(&self, key: &ClientKey) -> Result<reqwest::Client, AppError> {
+        Ok(self.client_builder(key)?.build()?)
+    }
+
+    /// a client left out of the cache which connects to the proxy at `addr`, the address its
+    /// host was checked against, instead of resolving the host again
+    pub fn build_pinned_proxy_client(
+        &self,
+        proxy: &ProxySettings,
+        addr: SocketAddr,
+    ) -> Result<reqwest::Client, AppError> {
+        // socks and plain http proxies are dialed by address, an https proxy keeps its host
+        // name for tls which is resolved to the address
+        let pinned = match proxy.scheme {
+            ProxyScheme::Https => proxy.clone(),
+            _ => ProxySettings {
+                host: addr.ip().to_string(),
+                ..proxy.clone()
+            },
+        };
+        let key = ClientKey {
+            proxy_server: Some(pinned.server_url()),
+            proxy_auth: pinned.auth_header(),
+            ..ClientKey::new(&self.config)
+        };
+        Ok(self
+            .client_builder(&key)?
+            .resolve(&proxy.host, addr)
+            .build()?)
+    }
+
+    fn client_builder(&self, key: &ClientKey) -> Result<reqwest::ClientBuilder, AppError> {
+        let mut client_builder = reqwest::ClientBuilder::new()
+            .connect_timeout(key.connect_timeout)
+            .read_timeout(key.read_timeout)
+            .danger_accept_invalid_certs(self.config.accept_invalid_certs);
+        for cert in &self.config.ca_certificates {
+            client_builder = client_builder.add_root_certificate(cert.clone());
+        }
+        if let Some(user_agent) = &key.user_agent {
+            client_builder = client_builder.user_agent(user_agent);
+        }
+        if let Some(proxy_server) = &key.proxy_server {
+            let settings = ProxySettings::from_columns(proxy_server, key.proxy_auth.as_deref())?;
+            let mut proxy = reqwest::Proxy::all(settings.server_url())?;
+            if let Some(username) = &settings.username {
+                proxy = proxy.basic_auth(username, settings.password.as_deref().unwrap_or(""));
+            } else if let Some(header) = key.proxy_auth.as_deref().and_then(legacy_auth_header) {
+                proxy = proxy.custom_http_auth(header);
+            }
+            client_builder = client_builder.proxy(proxy);
+        }
+        Ok(client_builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ConfigError;
+
+    #[test]
+    fn test_fetcher_caches_clients() -> anyhow::Result<()> {
+        let fetcher = Fetcher::new(FetchConfig::default());
+        let key = ClientKey {
+            user_agent: Some(String::from("clash")),
+            proxy_server: Some(String::from("socks5://127.0.0.1:1080")),
+            ..ClientKey::new(fetcher.config())
+        };
+        fetcher.client(key.clone())?;
+        fetcher.client(key)?;
+        fetcher.client(ClientKey::new(fetcher.config()))?;
+        assert_eq!(fetcher.clients.lock().unwrap().len(), 2);
+
+        for port in 0..CLIENT_CACHE_CAPACITY {
+            fetcher.client(ClientKey {
+                proxy_server: Some(format!("http://127.0.0.1:{}", 10000 + port)),
+                ..ClientKey::new(fetcher.config())
+            })?;
+        }
+        let clients = fetcher.clients.lock().unwrap();
+        assert_eq!(clients.len(), CLIENT_CACHE_CAPACITY);
+        assert!(!clients.contains_key(&ClientKey {
+            user_agent: Some(String::from("clash")),
+            proxy_server: Some(String::from("socks5://127.0.0.1:1080")),
+            ..ClientKey::new(fetcher.config())
+        }));
+        drop(clients);
+
+        let key = ClientKey {
+            proxy_server: Some(String::from("http://127.0.0.1:8080")),
+            proxy_auth: Some(String::from("Bearer token")),
+            ..ClientKey::new(fetcher.config())
+        };
+        fetcher.build_client(&key)?;
+        let key = ClientKey {
+            proxy_auth: Some(String::from("Bearer\ntoken")),
+            ..key
+        };
+        assert!(matches!(
+            fetcher.client(key),
+            Err(AppError::Config(ConfigError::UpstreamProxyInvalid { .. }))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+pub mod client;
+pub mod proxy;
+
+use crate::clash::parse_subscription_content;
+use crate::config::FetchConfig;
+use crate::error::{ConfigError, SyncError};
+use crate::models::subscribe_source::RequestHeaders;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header};
+
+pub use client::{ClientKey, Fetcher};
+pub use proxy::{ProxyScheme, ProxySettings};
+
+/// a response read in full
+#[derive(Clone, Debug)]
+pub struct FetchedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+/// headers set by the fetch itself, which a subscribe source can not replace
+const RESERVED_HEADERS: [HeaderName; 7] = [
+    header::HOST,
+    header::CONNECTION,
+    header::CONTENT_LENGTH,
+    header::TRANSFER_ENCODING,
+    header::PROXY_AUTHORIZATION,
+    header::IF_NONE_MATCH,
+    header::IF_MODIFIED_SINCE,
+];
+
+/// validate the plain text request headers of a subscribe source
+pub fn parse_request_headers(headers: &RequestHeaders) -> Result<HeaderMap, ConfigError> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in &headers.0 {
+        let invalid = |reason: &str| ConfigError::RequestHeaderInvalid {
+            name: name.clone(),
+            reason: reason.to_string(),
+        };
+        let header_name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e.to_string()))?;
+        if RESERVED_HEADERS.contains(&header_name) {
+            return Err(invalid("reserved header"));
+        }
+        if header_map.contains_key(&header_name) {
+            return Err(invalid("duplicated header"));
+        }
+        let header_value = HeaderValue::from_str(value).map_err(|e| invalid(&e.to_string()))?;
+        header_map.insert(header_name, header_value);
+    }
+    Ok(header_map)
+}
+
+/// successful and not modified responses pass, others are classified by status
+pub fn check_status(status: StatusCode) -> Result<(), SyncError> {
+    match status {
+        s if s.is_success() || s == StatusCode::NOT_MODIFIED => Ok(()),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::PAYMENT_REQUIRED => {
+            Err(SyncError::Unauthorized { status })
+        }
+        StatusCode::NOT_FOUND | StatusCode::GONE => Err(SyncError::Expired { status }),
+        s if s.is_client_error() => Err(SyncError::ClientError { status }),
+        s if s.is_server_error() => Err(SyncError::ServerError { status }),
+        _ => Err(SyncError::UnexpectedStatus { status }),
+    }
+}
+
+fn is_error_page_content_type(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    matches!(mime.as_str(), "text/html" | "application/xhtml+xml")
+        || ["image/", "audio/", "video/"]
+            .iter()
+            .any(|prefix| mime.starts_with(prefix))
+}
+
+fn is_html_document(body: &str) -> bool {
+    let head = body
+        .trim_start()
+        .chars()
+        .take(15)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    head.starts_with("<!doctype html") || head.starts_with("<html")
+}
+
+/// refuse a successful response that can not be a subscription, so it never replaces
+/// the content of the previous sync
+pub fn check_subscription_response(
+    config_name: &str,
+    res: &FetchedResponse,
+    min_body_size: usize,
+) -> Result<(), SyncError> {
+    if let Some(content_type) = res.headers.get(header::CONTENT_TYPE)
+        && is_error_page_content_type(content_type.to_str().unwrap_or_default())
+    {
+        return Err(SyncError::UnexpectedContentType {
+            content_type: content_type.clone(),
+        });
+    }
+    let size = res.body.trim().len();
+    if size < min_body_size {
+        return Err(SyncError::BodyTooSmall { size });
+    }
+    if is_html_document(&res.body) {
+        return Err(SyncError::UnexpectedContentType {
+            content_type: header::HeaderValue::from_static("text/html"),
+        });
+    }
+    parse_subscription_content(config_name, &res.body)
+        .map_err(|source| SyncError::InvalidContent { source })?;
+    Ok(())
+}
+
+async fn fetch_once(
+    request: reqwest::RequestBuilder,
+    max_body_size: usize,
+) -> Result<FetchedResponse, SyncError> {
+    let mut res = request.send().await?;
+    check_status(res.status())?;
+    if res
+        .content_length()
+        .is_some_and(|len| len > max_body_size as u64)
+    {
+        return Err(SyncError::BodyTooLarge {
+            limit: max_body_size,
+        });
+    }
+    let status = res.status();
+    let headers = res.headers().clone();
+    // the content length may be absent or lie, so count while reading
+    let mut body = vec![];
+    while let Some(chunk) = res.chunk().await? {
+        if body.len() + chunk.len() > max_body_size {
+            return Err(SyncError::BodyTooLarge {
+                limit: max_body_size,
+            });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(FetchedResponse {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// send the request, retrying timeouts and server errors with a jittered exponential backoff
+pub async fn fetch_with_retry(
+    request: reqwest::RequestBuilder,
+    config: &FetchConfig,
+) -> Result<FetchedResponse, SyncError> {
+    // a server trickling the body resets the read timeout on every chunk
+    let request = request.timeout(config.total_timeout);
+    let mut attempt = 0;
+    loop {
+        let Some(this_request) = request.try_clone() else {
+            return fetch_once(request, config.max_body_size).await;
+        };
+        match fetch_once(this_request, config.max_body_size).await {
+            Err(err) if attempt < config.max_retries && err.is_retryable() => {
+                let delay = config.backoff(attempt);
+                tracing::debug!("fetch failed, retry in {:?}: {}", delay, err);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content_type: &str, body: &str) -> FetchedResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, content_type.parse().unwrap());
+        FetchedResponse {
+            status: StatusCode::OK,
+            headers,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_request_headers() -> anyhow::Result<()> {
+        let headers = |pairs: &[(&str, &str)]| {
+            RequestHeaders(
+                pairs
+                    .iter()
+                    .map(|(n, v)| (n.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+        let header_map = parse_request_headers(&headers(&[
+            ("Accept", "text/yaml"),
+            ("x-api-token", "secret"),
+        ]))?;
+        assert_eq!(header_map[header::ACCEPT], "text/yaml");
+        assert_eq!(header_map["X-Api-Token"], "secret");
+
+        for pairs in [
+            [("bad name", "value")],
+            [("x-token", "bad\nvalue")],
+            [("Host", "example.com")],
+        ] {
+            assert!(matches!(
+                parse_request_headers(&headers(&pairs)),
+                Err(ConfigError::RequestHeaderInvalid { .. })
+            ));
+        }
+        assert!(parse_request_headers(&headers(&[("a", "1"), ("A", "2")])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_status() {
+        assert!(check_status(StatusCode::OK).is_ok());
+        assert!(check_status(StatusCode::NOT_MODIFIED).is_ok());
+        assert!(matches!(
+            check_status(StatusCode::FORBIDDEN),
+            Err(SyncError::Unauthorized { .. })
+        ));
+        assert!(matches!(
+            check_status(StatusCode::GONE),
+            Err(SyncError::Expired { .. })
+        ));
+        assert!(matches!(
+            check_status(StatusCode::TOO_MANY_REQUESTS),
+            Err(SyncError::ClientError { .. })
+        ));
+        let err = check_status(StatusCode::BAD_GATEWAY).unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(err.status(), Some(StatusCode::BAD_GATEWAY));
+    }
+
+    #[test]
+    fn test_check_subscription_response() {
+        let yaml = r#"
+proxies:
+  - { name: A, type: socks5, server: a.example.com, port: 1 }
+proxy-groups: []
+rules: []
+"#;
+        assert!(check_subscription_response("a", &response("text/yaml", yaml), 32).is_ok());
+        assert!(matches!(
+            check_subscription_response("a", &response("text/html; charset=utf-8", yaml), 32),
+            Err(SyncError::UnexpectedContentType { .. })
+        ));
+        assert!(matches!(
+            check_subscription_response(
+                "a",
+                &response("text/plain", "<!DOCTYPE html><html>token expired</html>"),
+                32
+            ),
+            Err(SyncError::UnexpectedContentType { .. })
+        ));
+        assert!(matches!(
+            check_subscription_response("a", &response("text/plain", "expired"), 32),
+            Err(SyncError::BodyTooSmall { size: 7 })
+        ));
+        assert!(matches!(
+            check_subscription_response("a", &response("text/plain", "proxies: {not: [a list"), 8),
+            Err(SyncError::InvalidContent { .. })
+        ));
+    }
+}
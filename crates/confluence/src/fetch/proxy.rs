@@ -0,0 +1,314 @@
+use crate::error::ConfigError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use percent_encoding::percent_decode_str;
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ProxyScheme {
+    Http,
+    Https,
+    Socks5,
+    /// socks5 resolving host names on the proxy
+    Socks5h,
+}
+
+impl ProxyScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+            Self::Socks5 => "socks5",
+            Self::Socks5h => "socks5h",
+        }
+    }
+
+    fn parse(scheme: &str) -> Option<Self> {
+        match scheme {
+            "http" => Some(Self::Http),
+            "https" => Some(Self::Https),
+            "socks5" => Some(Self::Socks5),
+            "socks5h" => Some(Self::Socks5h),
+            _ => None,
+        }
+    }
+
+    fn default_port(&self) -> u16 {
+        match self {
+            Self::Http => 80,
+            Self::Https => 443,
+            Self::Socks5 | Self::Socks5h => 1080,
+        }
+    }
+}
+
+/// an upstream proxy subscriptions are fetched through, stored as the `proxy_server` url
+/// and the `proxy_auth` basic credentials of a subscribe source, other `proxy_auth` schemes
+/// stored before are sent as they are
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProxySettings {
+    pub scheme: ProxyScheme,
+    pub host: String,
+    pub port: u16,
+    #[ts(optional)]
+    pub username: Option<String>,
+    #[ts(optional)]
+    pub password: Option<String>,
+}
+
+impl ProxySettings {
+    /// read the stored columns back, a server without scheme is taken for http
+    pub fn from_columns(proxy_server: &str, proxy_auth: Option<&str>) -> Result<Self, ConfigError> {
+        let invalid = |reason: &str| ConfigError::UpstreamProxyInvalid {
+            proxy: proxy_server.to_string(),
+            reason: reason.to_string(),
+        };
+        let server = if proxy_server.contains("://") {
+            proxy_server.to_string()
+        } else {
+            format!("http://{}", proxy_server)
+        };
+        let url = url::Url::parse(&server).map_err(|e| invalid(&e.to_string()))?;
+        let scheme = ProxyScheme::parse(url.scheme()).ok_or_else(|| invalid("unknown scheme"))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| invalid("missing host"))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        let (username, password) = if !url.username().is_empty() {
+            (Some(decode(url.username())), url.password().map(decode))
+        } else if let Some(proxy_auth) = proxy_auth.filter(|a| !a.is_empty()) {
+            match decode_basic_auth(proxy_auth) {
+                Some((username, password)) => (Some(username), Some(password)),
+                None => {
+                    legacy_auth_header(proxy_auth).ok_or_else(|| invalid("invalid proxy auth"))?;
+                    (None, None)
+                }
+            }
+        } else {
+            (None, None)
+        };
+        let settings = Self {
+            port: url.port().unwrap_or_else(|| scheme.default_port()),
+            scheme,
+            host,
+            username,
+            password,
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| ConfigError::UpstreamProxyInvalid {
+            proxy: format!("{}://{}:{}", self.scheme.as_str(), self.host, self.port),
+            reason: reason.to_string(),
+        };
+        if self.host.is_empty()
+            || self
+                .host
+                .contains(|c: char| c.is_whitespace() || matches!(c, '/' | '@' | '?' | '#'))
+        {
+            return Err(invalid("invalid host"));
+        }
+        if self.port == 0 {
+            return Err(invalid("invalid port"));
+        }
+        match (&self.username, &self.password) {
+            (None, Some(_)) => Err(invalid("password without username")),
+            (Some(username), _) if username.is_empty() || username.contains(':') => {
+                Err(invalid("username must be non empty without colons"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// the address to connect to the proxy at, `None` if the host is loopback, link local,
+    /// private or unresolvable, a host name counts as private if any of its addresses is
+    pub async fn resolve_public_addr(&self) -> Option<SocketAddr> {
+        if let Ok(ip) = self.host.parse::<IpAddr>() {
+            return (!is_private_ip(ip)).then_some(SocketAddr::new(ip, self.port));
+        }
+        let addrs = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .ok()?
+            .collect::<Vec<_>>();
+        if addrs.iter().any(|addr| is_private_ip(addr.ip())) {
+            return None;
+        }
+        addrs.into_iter().next()
+    }
+
+    /// stored as `proxy_server`, without credentials
+    pub fn server_url(&self) -> String {
+        if self.host.contains(':') {
+            format!("{}://[{}]:{}", self.scheme.as_str(), self.host, self.port)
+        } else {
+            format!("{}://{}:{}", self.scheme.as_str(), self.host, self.port)
+        }
+    }
+
+    /// stored as `proxy_auth`, a basic authorization header value
+    pub fn auth_header(&self) -> Option<String> {
+        self.username.as_ref().map(|username| {
+            let credentials = format!("{}:{}", username, self.password.as_deref().unwrap_or(""));
+            format!("Basic {}", STANDARD.encode(credentials))
+        })
+    }
+}
+
+/// a `proxy_auth` of another scheme than basic, kept from before credentials were structured
+pub fn legacy_auth_header(proxy_auth: &str) -> Option<HeaderValue> {
+    if proxy_auth.is_empty() || decode_basic_auth(proxy_auth).is_some() {
+        return None;
+    }
+    HeaderValue::from_str(proxy_auth).ok()
+}
+
+fn is_private_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // shared address space of carrier grade nat, 100.64.0.0/10
+        || (a == 100 && b & 0xc0 == 64)
+        // documentation, 192.0.2.0/24, 198.51.100.0/24 and 203.0.113.0/24
+        || matches!((a, b, c), (192, 0, 2) | (198, 51, 100) | (203, 0, 113))
+}
+
+fn is_private_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    if let Some(ip) = ip.to_ipv4_mapped() {
+        return is_private_ipv4(ip);
+    }
+    // nat64 well known prefix 64:ff9b::/96 reaches the embedded ipv4 address
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., hi, lo] = segments;
+        return is_private_ipv4(Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo)));
+    }
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // local use nat64 prefix 64:ff9b:1::/48
+        || segments[..3] == [0x64, 0xff9b, 1]
+        // documentation, 2001:db8::/32 and 3fff::/20
+        || segments[..2] == [0x2001, 0xdb8]
+        || segments[0] >> 4 == 0x3ff
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => is_private_ipv6(ip),
+    }
+}
+
+fn decode_basic_auth(header: &str) -> Option<(String, String)> {
+    let (kind, credentials) = header.trim().split_once(' ')?;
+    if !kind.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let credentials = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (username, password) = credentials.split_once(':')?;
+    Some((username.to_string(), password.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_settings_columns() -> anyhow::Result<()> {
+        let settings = ProxySettings {
+            scheme: ProxyScheme::Socks5,
+            host: String::from("proxy.example.com"),
+            port: 1080,
+            username: Some(String::from("user")),
+            password: Some(String::from("p@ss:word")),
+        };
+        settings.validate()?;
+        let auth = settings.auth_header();
+        assert_eq!(
+            ProxySettings::from_columns(&settings.server_url(), auth.as_deref())?,
+            settings
+        );
+
+        let legacy = ProxySettings::from_columns("127.0.0.1:7890", None)?;
+        assert_eq!(legacy.scheme, ProxyScheme::Http);
+        assert_eq!(legacy.port, 7890);
+
+        let in_url = ProxySettings::from_columns("socks5h://a%40b:c@[::1]", None)?;
+        assert_eq!(in_url.host, "::1");
+        assert_eq!(in_url.port, 1080);
+        assert_eq!(in_url.username.as_deref(), Some("a@b"));
+        assert_eq!(in_url.server_url(), "socks5h://[::1]:1080");
+
+        assert!(ProxySettings::from_columns("ftp://example.com", None).is_err());
+        let bearer = ProxySettings::from_columns("http://example.com", Some("Bearer token"))?;
+        assert_eq!(bearer.username, None);
+        assert!(legacy_auth_header("Bearer token").is_some());
+        assert!(legacy_auth_header("Basic dXNlcjpwYXNz").is_none());
+        assert!(ProxySettings::from_columns("http://example.com", Some("Bearer\ntoken")).is_err());
+        assert!(
+            ProxySettings {
+                username: None,
+                ..settings
+            }
+            .validate()
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_private_proxy_hosts() {
+        let proxy = |host: &str| ProxySettings {
+            scheme: ProxyScheme::Socks5,
+            host: host.to_string(),
+            port: 1080,
+            username: None,
+            password: None,
+        };
+        for host in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "100.127.255.254",
+            "203.0.113.1",
+            "::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+            "64:ff9b:1::1",
+            "2001:db8::1",
+            "3fff::1",
+        ] {
+            assert_eq!(proxy(host).resolve_public_addr().await, None, "{}", host);
+        }
+        for host in [
+            "1.1.1.1",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "64:ff9b::101:101",
+        ] {
+            assert!(
+                proxy(host).resolve_public_addr().await.is_some(),
+                "{}",
+                host
+            );
+        }
+    }
+}
@@ -1,13 +1,14 @@
-#![feature(slice_take)]
 #![feature(iter_intersperse)]
 #![feature(addr_parse_ascii)]
-#![feature(assert_matches)]
 
 pub mod auth;
 pub mod clash;
-pub mod config;
+pub mod config;
+pub mod convert;
+pub mod crypto;
 pub mod dto;
 pub mod error;
+pub mod fetch;
 pub mod migrations;
 pub mod models;
 pub mod mux;
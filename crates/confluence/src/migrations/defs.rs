@@ -21,6 +21,10 @@ pub enum Confluence {
     CronErr,
     CronNextAt,
     UserAgent,
+    SourceAutoGroup,
+    TemplateId,
+    DegradedMux,
+    MuxFailures,
 }
 
 #[derive(DeriveIden)]
@@ -31,6 +35,8 @@ pub enum Profile {
     CreatedAt,
     UpdatedAt,
     ResourceToken,
+    Target,
+    RevisionId,
 }
 
 #[derive(DeriveIden)]
@@ -50,6 +56,71 @@ pub enum SubscribeSource {
     PassiveSync,
     ProxyServer,
     ProxyAuth,
+    ImportProxyGroups,
+    ProxyInclude,
+    ProxyExclude,
+    ProxyRename,
+    Tags,
+    PinnedSnapshotId,
+    LastSyncAt,
+    LastSyncStatus,
+    LastError,
+    ConsecutiveFailures,
+    FetchOverrides,
+    Etag,
+    LastModified,
+    RequestHeaders,
+}
+
+#[derive(DeriveIden)]
+pub enum SubscribeSourceSnapshot {
+    Table,
+    Id,
+    SubscribeSourceId,
+    Content,
+    Status,
+    SubUpload,
+    SubDownload,
+    SubTotal,
+    SubExpire,
+    FetchedAt,
+    ContentHash,
+}
+
+#[derive(DeriveIden)]
+pub enum ConfluenceRevision {
+    Table,
+    Id,
+    ConfluenceId,
+    Content,
+    ContentHash,
+    Sources,
+    DiffStats,
+    Diff,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum Template {
+    Table,
+    Id,
+    Name,
+    Creator,
+    Content,
+    Shared,
+    Version,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum TemplateVersion {
+    Table,
+    Id,
+    TemplateId,
+    Version,
+    Content,
+    CreatedAt,
 }
 
 pub async fn create_postgres_auto_update_ts_fn(
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::Profile;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Profile::Target)
+                            .text()
+                            .not_null()
+                            .default("clash"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .drop_column(Profile::Target)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
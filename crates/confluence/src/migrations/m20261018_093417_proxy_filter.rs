@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::SubscribeSource;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::ProxyInclude).json_binary(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::ProxyExclude).json_binary(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::ProxyRename).json_binary(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .drop_column(SubscribeSource::ProxyInclude)
+                    .drop_column(SubscribeSource::ProxyExclude)
+                    .drop_column(SubscribeSource::ProxyRename)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::{
+    Confluence, Template, TemplateVersion, create_postgres_auto_update_ts_trigger,
+    drop_postgres_auto_update_ts_trigger,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Template::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Template::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Template::Name).string().not_null())
+                    .col(ColumnDef::new(Template::Creator).string().not_null())
+                    .col(ColumnDef::new(Template::Content).text().not_null())
+                    .col(
+                        ColumnDef::new(Template::Shared)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Template::Version)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(Template::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Template::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        create_postgres_auto_update_ts_trigger(manager, "updated_at", "template").await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TemplateVersion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TemplateVersion::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TemplateVersion::TemplateId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("template_version_template_id_fk")
+                            .from(TemplateVersion::Table, TemplateVersion::TemplateId)
+                            .to(Template::Table, Template::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(TemplateVersion::Version)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TemplateVersion::Content).text().not_null())
+                    .col(
+                        ColumnDef::new(TemplateVersion::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .if_not_exists()
+                            .name("template_version_template_id_version_index")
+                            .col(TemplateVersion::TemplateId)
+                            .col(TemplateVersion::Version)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Confluence::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Confluence::TemplateId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("confluence_template_id_fk")
+                            .from_tbl(Confluence::Table)
+                            .from_col(Confluence::TemplateId)
+                            .to_tbl(Template::Table)
+                            .to_col(Template::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Confluence::Table)
+                    .drop_foreign_key(Alias::new("confluence_template_id_fk"))
+                    .drop_column(Confluence::TemplateId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TemplateVersion::Table).to_owned())
+            .await?;
+        drop_postgres_auto_update_ts_trigger(manager, "updated_at", "template").await?;
+        manager
+            .drop_table(Table::drop().table(Template::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
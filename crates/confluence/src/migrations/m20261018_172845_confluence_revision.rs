@@ -0,0 +1,104 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::{Confluence, ConfluenceRevision, Profile};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ConfluenceRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::ConfluenceId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("confluence_revision_confluence_id_fk")
+                            .from(ConfluenceRevision::Table, ConfluenceRevision::ConfluenceId)
+                            .to(Confluence::Table, Confluence::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::Content)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::ContentHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::Sources)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ConfluenceRevision::DiffStats).json_binary())
+                    .col(
+                        ColumnDef::new(ConfluenceRevision::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .if_not_exists()
+                            .name("confluence_revision_confluence_id_index")
+                            .col(ConfluenceRevision::ConfluenceId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Profile::RevisionId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("profile_revision_id_fk")
+                            .from_tbl(Profile::Table)
+                            .from_col(Profile::RevisionId)
+                            .to_tbl(ConfluenceRevision::Table)
+                            .to_col(ConfluenceRevision::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Profile::Table)
+                    .drop_foreign_key(Alias::new("profile_revision_id_fk"))
+                    .drop_column(Profile::RevisionId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ConfluenceRevision::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::{SubscribeSource, SubscribeSourceSnapshot};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubscribeSourceSnapshot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SubscribeSourceSnapshot::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubscribeSourceSnapshot::SubscribeSourceId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("subscribe_source_snapshot_subscribe_source_id_fk")
+                            .from(
+                                SubscribeSourceSnapshot::Table,
+                                SubscribeSourceSnapshot::SubscribeSourceId,
+                            )
+                            .to(SubscribeSource::Table, SubscribeSource::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(SubscribeSourceSnapshot::Content)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubscribeSourceSnapshot::Status)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SubscribeSourceSnapshot::SubUpload).big_integer())
+                    .col(ColumnDef::new(SubscribeSourceSnapshot::SubDownload).big_integer())
+                    .col(ColumnDef::new(SubscribeSourceSnapshot::SubTotal).big_integer())
+                    .col(ColumnDef::new(SubscribeSourceSnapshot::SubExpire).timestamp())
+                    .col(
+                        ColumnDef::new(SubscribeSourceSnapshot::FetchedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .if_not_exists()
+                            .name("subscribe_source_snapshot_subscribe_source_id_index")
+                            .col(SubscribeSourceSnapshot::SubscribeSourceId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::PinnedSnapshotId).integer(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("subscribe_source_pinned_snapshot_id_fk")
+                            .from_tbl(SubscribeSource::Table)
+                            .from_col(SubscribeSource::PinnedSnapshotId)
+                            .to_tbl(SubscribeSourceSnapshot::Table)
+                            .to_col(SubscribeSourceSnapshot::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .drop_foreign_key(Alias::new("subscribe_source_pinned_snapshot_id_fk"))
+                    .drop_column(SubscribeSource::PinnedSnapshotId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(SubscribeSourceSnapshot::Table)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
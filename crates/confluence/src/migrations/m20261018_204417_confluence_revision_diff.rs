@@ -0,0 +1,35 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::ConfluenceRevision;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ConfluenceRevision::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ConfluenceRevision::Diff).json_binary(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ConfluenceRevision::Table)
+                    .drop_column(ConfluenceRevision::Diff)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::SubscribeSource;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::LastSyncAt).timestamp(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::LastSyncStatus).integer(),
+                    )
+                    .add_column_if_not_exists(ColumnDef::new(SubscribeSource::LastError).text())
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSource::ConsecutiveFailures)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSource::Table)
+                    .drop_column(SubscribeSource::LastSyncAt)
+                    .drop_column(SubscribeSource::LastSyncStatus)
+                    .drop_column(SubscribeSource::LastError)
+                    .drop_column(SubscribeSource::ConsecutiveFailures)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
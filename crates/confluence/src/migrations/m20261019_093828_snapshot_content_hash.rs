@@ -0,0 +1,35 @@
+use sea_orm_migration::prelude::*;
+
+use super::defs::SubscribeSourceSnapshot;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSourceSnapshot::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubscribeSourceSnapshot::ContentHash).text(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubscribeSourceSnapshot::Table)
+                    .drop_column(SubscribeSourceSnapshot::ContentHash)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
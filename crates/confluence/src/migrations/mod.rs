@@ -9,6 +9,21 @@ mod m20240213_092818_custom_ua;
 mod m20250127_043332_passive_sync;
 mod m20250129_025213_subscriber_source_proxy;
 mod m20250207_005800_fix_deletions;
+mod m20261018_021530_profile_target;
+mod m20261018_074205_import_proxy_groups;
+mod m20261018_093417_proxy_filter;
+mod m20261018_112608_source_auto_group;
+mod m20261018_130951_subscribe_source_tags;
+mod m20261018_151204_template_library;
+mod m20261018_172845_confluence_revision;
+mod m20261018_190512_subscribe_source_snapshot;
+mod m20261018_204417_confluence_revision_diff;
+mod m20261018_221036_degraded_mux;
+mod m20261018_234150_subscribe_source_sync_status;
+mod m20261019_012304_fetch_overrides;
+mod m20261019_031547_conditional_fetch;
+mod m20261019_074536_subscribe_source_request_headers;
+mod m20261019_093828_snapshot_content_hash;
 
 pub struct Migrator;
 
@@ -24,6 +39,21 @@ impl MigratorTrait for Migrator {
             Box::new(m20250127_043332_passive_sync::Migration),
             Box::new(m20250129_025213_subscriber_source_proxy::Migration),
             Box::new(m20250207_005800_fix_deletions::Migration),
+            Box::new(m20261018_021530_profile_target::Migration),
+            Box::new(m20261018_074205_import_proxy_groups::Migration),
+            Box::new(m20261018_093417_proxy_filter::Migration),
+            Box::new(m20261018_112608_source_auto_group::Migration),
+            Box::new(m20261018_130951_subscribe_source_tags::Migration),
+            Box::new(m20261018_151204_template_library::Migration),
+            Box::new(m20261018_172845_confluence_revision::Migration),
+            Box::new(m20261018_190512_subscribe_source_snapshot::Migration),
+            Box::new(m20261018_204417_confluence_revision_diff::Migration),
+            Box::new(m20261018_221036_degraded_mux::Migration),
+            Box::new(m20261018_234150_subscribe_source_sync_status::Migration),
+            Box::new(m20261019_012304_fetch_overrides::Migration),
+            Box::new(m20261019_031547_conditional_fetch::Migration),
+            Box::new(m20261019_074536_subscribe_source_request_headers::Migration),
+            Box::new(m20261019_093828_snapshot_content_hash::Migration),
         ]
     }
 }
@@ -1,4 +1,47 @@
+use sea_orm::FromJsonQueryResult;
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum AutoGroupKind {
+    UrlTest,
+    Fallback,
+    LoadBalance,
+}
+
+/// an extra health checked group generated for every subscribe source when muxing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct SourceAutoGroup {
+    pub kind: AutoGroupKind,
+    #[ts(optional)]
+    pub url: Option<String>,
+    /// seconds between health checks
+    #[ts(optional)]
+    pub interval: Option<u32>,
+    /// milliseconds, only used by url-test
+    #[ts(optional)]
+    pub tolerance: Option<u32>,
+}
+
+/// a subscribe source left out of or muxed from an older snapshot in a degraded mux
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct MuxFailure {
+    pub subscribe_source_id: i32,
+    pub name: String,
+    pub reason: String,
+    /// the last valid snapshot muxed instead, none if the source was skipped
+    #[ts(optional)]
+    pub fallback_snapshot_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct MuxFailures(pub Vec<MuxFailure>);
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "confluence")]
@@ -29,6 +72,15 @@ pub struct Model {
     pub cron_next_at: Option<DateTime>,
     #[sea_orm(column_type = "Text")]
     pub user_agent: String,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub source_auto_group: Option<SourceAutoGroup>,
+    /// the library template to mux with instead of the inline `template`
+    pub template_id: Option<i32>,
+    /// mux the other sources when one is broken instead of failing the whole mux
+    pub degraded_mux: bool,
+    /// sources which failed in the latest mux
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub mux_failures: Option<MuxFailures>,
 }
 
 impl Model {
@@ -43,10 +95,26 @@ impl Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::confluence_revision::Entity")]
+    ConfluenceRevision,
     #[sea_orm(has_many = "super::profile::Entity")]
     Profile,
     #[sea_orm(has_many = "super::subscribe_source::Entity")]
     SubscribeSource,
+    #[sea_orm(
+        belongs_to = "super::template::Entity",
+        from = "Column::TemplateId",
+        to = "super::template::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Template,
+}
+
+impl Related<super::confluence_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ConfluenceRevision.def()
+    }
 }
 
 impl Related<super::profile::Entity> for Entity {
@@ -61,4 +129,10 @@ impl Related<super::subscribe_source::Entity> for Entity {
     }
 }
 
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,111 @@
+use sea_orm::FromJsonQueryResult;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// a subscribe source as it was muxed into a revision
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct RevisionSource {
+    pub subscribe_source_id: i32,
+    pub name: String,
+    /// the fetched payload the source was muxed from, if it was kept
+    #[ts(optional)]
+    pub snapshot_id: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct RevisionSources(pub Vec<RevisionSource>);
+
+/// changed lines of the mux content against the previous revision
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS,
+)]
+#[ts(export)]
+pub struct DiffStats {
+    pub added_lines: u32,
+    pub removed_lines: u32,
+}
+
+/// a proxy in both configs with changed settings, matched by name or else by server and port
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProxyChange {
+    pub name: String,
+    /// set if the proxy was matched by server and port under another name
+    #[ts(optional)]
+    pub previous_name: Option<String>,
+    /// changed settings other than the name
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct GroupChange {
+    pub name: String,
+    pub added_members: Vec<String>,
+    pub removed_members: Vec<String>,
+}
+
+/// what changed between two muxed configs
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct MuxDiff {
+    pub added_proxies: Vec<String>,
+    pub removed_proxies: Vec<String>,
+    pub modified_proxies: Vec<ProxyChange>,
+    pub added_groups: Vec<String>,
+    pub removed_groups: Vec<String>,
+    pub changed_groups: Vec<GroupChange>,
+    pub added_rules: Vec<String>,
+    pub removed_rules: Vec<String>,
+}
+
+impl MuxDiff {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "confluence_revision")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub confluence_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    /// hex sha256 of the content
+    pub content_hash: String,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub sources: RevisionSources,
+    /// none for the first revision of a confluence
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub diff_stats: Option<DiffStats>,
+    /// none for the first revision of a confluence or if a content is no clash config
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub diff: Option<MuxDiff>,
+    #[sea_orm(column_type = "Timestamp")]
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::confluence::Entity",
+        from = "Column::ConfluenceId",
+        to = "super::confluence::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Confluence,
+}
+
+impl Related<super::confluence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Confluence.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -3,5 +3,9 @@
 pub mod prelude;
 
 pub mod confluence;
+pub mod confluence_revision;
 pub mod profile;
 pub mod subscribe_source;
+pub mod subscribe_source_snapshot;
+pub mod template;
+pub mod template_version;
@@ -1,5 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.4
 
 pub use super::confluence::Entity as Confluence;
+pub use super::confluence_revision::Entity as ConfluenceRevision;
 pub use super::profile::Entity as Profile;
 pub use super::subscribe_source::Entity as SubscribeSource;
+pub use super::subscribe_source_snapshot::Entity as SubscribeSourceSnapshot;
+pub use super::template::Entity as Template;
+pub use super::template_version::Entity as TemplateVersion;
@@ -2,6 +2,37 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    TS,
+)]
+#[sea_orm(rs_type = "String", db_type = "Text")]
+#[serde(rename_all = "kebab-case")]
+#[ts(export)]
+pub enum ProfileTarget {
+    #[default]
+    #[sea_orm(string_value = "clash")]
+    Clash,
+    #[sea_orm(string_value = "sing-box")]
+    SingBox,
+    #[sea_orm(string_value = "surge")]
+    Surge,
+    #[sea_orm(string_value = "stash")]
+    Stash,
+    #[sea_orm(string_value = "quantumult-x")]
+    QuantumultX,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "profile")]
@@ -14,6 +45,9 @@ pub struct Model {
     #[sea_orm(column_type = "Timestamp")]
     pub updated_at: DateTime,
     pub resource_token: String,
+    pub target: ProfileTarget,
+    /// serve this revision instead of the latest mux content
+    pub revision_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -26,6 +60,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Confluence,
+    #[sea_orm(
+        belongs_to = "super::confluence_revision::Entity",
+        from = "Column::RevisionId",
+        to = "super::confluence_revision::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    ConfluenceRevision,
 }
 
 impl Related<super::confluence::Entity> for Entity {
@@ -34,4 +76,10 @@ impl Related<super::confluence::Entity> for Entity {
     }
 }
 
+impl Related<super::confluence_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ConfluenceRevision.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,55 @@
+use sea_orm::FromJsonQueryResult;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// regexes matched against a proxy, a proxy matches if every given pattern matches,
+/// a matcher without any pattern is ignored
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct ProxyMatcher {
+    #[ts(optional)]
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    #[ts(optional)]
+    pub kind: Option<String>,
+    #[ts(optional)]
+    pub server: Option<String>,
+}
+
+/// replace every match of `pattern` in the proxy name, `replacement` may refer to groups like `$1`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ProxyRenameRule {
+    pub pattern: String,
+    pub replacement: String,
+}
+
+/// rename rules applied in order
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct ProxyRenameRules(pub Vec<ProxyRenameRule>);
+
+/// per source replacements of the fetch defaults
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct FetchOverrides {
+    #[ts(optional)]
+    pub connect_timeout_secs: Option<u32>,
+    #[ts(optional)]
+    pub read_timeout_secs: Option<u32>,
+    #[ts(optional)]
+    pub max_retries: Option<u32>,
+    /// bytes
+    #[ts(optional)]
+    pub max_body_size: Option<u32>,
+}
+
+/// extra headers sent when fetching, by name, the values are sealed at rest
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, FromJsonQueryResult, TS)]
+#[ts(export)]
+pub struct RequestHeaders(pub BTreeMap<String, String>);
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "subscribe_source")]
@@ -23,6 +73,37 @@ pub struct Model {
     pub passive_sync: Option<bool>,
     pub proxy_server: Option<String>,
     pub proxy_auth: Option<String>,
+    // keep the proxy groups of the source when muxing
+    pub import_proxy_groups: Option<bool>,
+    // only keep proxies matching
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub proxy_include: Option<ProxyMatcher>,
+    // drop proxies matching
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub proxy_exclude: Option<ProxyMatcher>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub proxy_rename: Option<ProxyRenameRules>,
+    // select the source in `<mux:tag=...>` template slots
+    pub tags: Vec<String>,
+    // mux this snapshot instead of the latest content
+    pub pinned_snapshot_id: Option<i32>,
+    #[sea_orm(column_type = "Timestamp")]
+    pub last_sync_at: Option<DateTime>,
+    // http status of the latest fetch, none if no response was received
+    pub last_sync_status: Option<i32>,
+    #[sea_orm(column_type = "Text")]
+    pub last_error: Option<String>,
+    // failed fetches since the latest successful one
+    pub consecutive_failures: i32,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub fetch_overrides: Option<FetchOverrides>,
+    // validators of the fetched content, sent back to skip unchanged payloads
+    #[sea_orm(column_type = "Text")]
+    pub etag: Option<String>,
+    #[sea_orm(column_type = "Text")]
+    pub last_modified: Option<String>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub request_headers: Option<RequestHeaders>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -35,6 +116,8 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Confluence,
+    #[sea_orm(has_many = "super::subscribe_source_snapshot::Entity")]
+    SubscribeSourceSnapshot,
 }
 
 impl Related<super::confluence::Entity> for Entity {
@@ -43,4 +126,10 @@ impl Related<super::confluence::Entity> for Entity {
     }
 }
 
+impl Related<super::subscribe_source_snapshot::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SubscribeSourceSnapshot.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
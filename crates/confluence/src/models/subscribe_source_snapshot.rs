@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+
+/// a payload fetched from the url of a subscribe source
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "subscribe_source_snapshot")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub subscribe_source_id: i32,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    /// http status of the fetch
+    pub status: i32,
+    pub sub_upload: Option<i64>,
+    pub sub_download: Option<i64>,
+    pub sub_total: Option<i64>,
+    pub sub_expire: Option<DateTime>,
+    #[sea_orm(column_type = "Timestamp")]
+    pub fetched_at: DateTime,
+    /// hex sha256 of the content, unset for snapshots taken before it was recorded
+    pub content_hash: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::subscribe_source::Entity",
+        from = "Column::SubscribeSourceId",
+        to = "super::subscribe_source::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    SubscribeSource,
+}
+
+impl Related<super::subscribe_source::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SubscribeSource.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
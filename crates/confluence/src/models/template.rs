@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+
+/// a clash template owned by its creator, confluences following it are muxed with
+/// its latest content
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "template")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub name: String,
+    pub creator: String,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    /// readable and followable by every user, only the creator may change it
+    pub shared: bool,
+    pub version: i32,
+    #[sea_orm(column_type = "Timestamp")]
+    pub created_at: DateTime,
+    #[sea_orm(column_type = "Timestamp")]
+    pub updated_at: DateTime,
+}
+
+impl Model {
+    pub fn is_visible_to(&self, user_id: &str) -> bool {
+        self.shared || self.creator == user_id
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::confluence::Entity")]
+    Confluence,
+    #[sea_orm(has_many = "super::template_version::Entity")]
+    TemplateVersion,
+}
+
+impl Related<super::confluence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Confluence.def()
+    }
+}
+
+impl Related<super::template_version::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TemplateVersion.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
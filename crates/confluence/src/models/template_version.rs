@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+
+/// the content of a template as of one of its versions
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "template_version")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub template_id: i32,
+    pub version: i32,
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+    #[sea_orm(column_type = "Timestamp")]
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::template::Entity",
+        from = "Column::TemplateId",
+        to = "super::template::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Template,
+}
+
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
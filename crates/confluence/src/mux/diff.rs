@@ -0,0 +1,180 @@
+use crate::clash::{ClashConfig, Proxy};
+use crate::models::confluence_revision::{GroupChange, MuxDiff, ProxyChange};
+use serde_yaml::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// settings of the proxies which differ, the name aside
+fn changed_fields(old: &Proxy, new: &Proxy) -> Vec<String> {
+    let to_mapping = |proxy: &Proxy| match serde_yaml::to_value(proxy) {
+        Ok(Value::Mapping(mapping)) => mapping,
+        _ => Default::default(),
+    };
+    let (old, new) = (to_mapping(old), to_mapping(new));
+    old.keys()
+        .chain(new.keys())
+        .filter_map(Value::as_str)
+        .filter(|key| *key != "name" && old.get(key) != new.get(key))
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// members of `new` missing in `old`, in order
+fn missing_in(old: &[String], new: &[String]) -> Vec<String> {
+    let old = old.iter().collect::<HashSet<_>>();
+    new.iter().filter(|n| !old.contains(n)).cloned().collect()
+}
+
+/// proxies, groups and rules added, removed or changed from `old` to `new`
+pub fn diff_configs(old: &ClashConfig, new: &ClashConfig) -> MuxDiff {
+    let mut diff = MuxDiff::default();
+
+    let old_proxies = old
+        .proxies
+        .iter()
+        .map(|p| (p.name(), p))
+        .collect::<HashMap<_, _>>();
+    let new_names = new.proxies.iter().map(|p| p.name()).collect::<HashSet<_>>();
+    let mut unmatched = old
+        .proxies
+        .iter()
+        .filter(|p| !new_names.contains(p.name()))
+        .collect::<Vec<_>>();
+    for proxy in &new.proxies {
+        if let Some(old_proxy) = old_proxies.get(proxy.name()) {
+            let fields = changed_fields(old_proxy, proxy);
+            if !fields.is_empty() {
+                diff.modified_proxies.push(ProxyChange {
+                    name: proxy.name().to_string(),
+                    previous_name: None,
+                    fields,
+                });
+            }
+        } else if let Some(index) = unmatched
+            .iter()
+            .position(|p| p.server() == proxy.server() && p.port() == proxy.port())
+        {
+            let old_proxy = unmatched.remove(index);
+            diff.modified_proxies.push(ProxyChange {
+                name: proxy.name().to_string(),
+                previous_name: Some(old_proxy.name().to_string()),
+                fields: changed_fields(old_proxy, proxy),
+            });
+        } else {
+            diff.added_proxies.push(proxy.name().to_string());
+        }
+    }
+    diff.removed_proxies = unmatched.iter().map(|p| p.name().to_string()).collect();
+
+    let old_groups = old
+        .proxy_groups
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<_, _>>();
+    let new_groups = new
+        .proxy_groups
+        .iter()
+        .map(|g| g.name.as_str())
+        .collect::<HashSet<_>>();
+    for group in &new.proxy_groups {
+        let Some(old_group) = old_groups.get(group.name.as_str()) else {
+            diff.added_groups.push(group.name.clone());
+            continue;
+        };
+        let added_members = missing_in(&old_group.proxies, &group.proxies);
+        let removed_members = missing_in(&group.proxies, &old_group.proxies);
+        if !added_members.is_empty() || !removed_members.is_empty() {
+            diff.changed_groups.push(GroupChange {
+                name: group.name.clone(),
+                added_members,
+                removed_members,
+            });
+        }
+    }
+    diff.removed_groups = old
+        .proxy_groups
+        .iter()
+        .filter(|g| !new_groups.contains(g.name.as_str()))
+        .map(|g| g.name.clone())
+        .collect();
+
+    let old_rules = old.rules.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    let new_rules = new.rules.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    diff.added_rules = missing_in(&old_rules, &new_rules);
+    diff.removed_rules = missing_in(&new_rules, &old_rules);
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_configs() -> anyhow::Result<()> {
+        let old: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: A, type: socks5, server: a.example.com, port: 1 }
+  - { name: B, type: socks5, server: b.example.com, port: 1 }
+  - { name: C, type: socks5, server: c.example.com, port: 1 }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [A, B, C] }
+  - { name: OLD, type: select, proxies: [A] }
+rules:
+  - DOMAIN-SUFFIX,google.com,PROXY
+  - MATCH,PROXY
+            "#,
+        )?;
+        let new: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: A, type: socks5, server: a.example.com, port: 2 }
+  - { name: B2, type: socks5, server: b.example.com, port: 1 }
+  - { name: D, type: socks5, server: d.example.com, port: 1 }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [A, B2, D] }
+  - { name: NEW, type: select, proxies: [A] }
+rules:
+  - DOMAIN-SUFFIX,github.com,PROXY
+  - MATCH,PROXY
+            "#,
+        )?;
+
+        let diff = diff_configs(&old, &new);
+
+        assert_eq!(diff.added_proxies, vec!["D"]);
+        assert_eq!(diff.removed_proxies, vec!["C"]);
+        assert_eq!(
+            diff.modified_proxies,
+            vec![
+                ProxyChange {
+                    name: "A".to_string(),
+                    previous_name: None,
+                    fields: vec!["port".to_string()],
+                },
+                ProxyChange {
+                    name: "B2".to_string(),
+                    previous_name: Some("B".to_string()),
+                    fields: vec![],
+                },
+            ]
+        );
+        assert_eq!(diff.added_groups, vec!["NEW"]);
+        assert_eq!(diff.removed_groups, vec!["OLD"]);
+        assert_eq!(
+            diff.changed_groups,
+            vec![GroupChange {
+                name: "PROXY".to_string(),
+                added_members: vec!["B2".to_string(), "D".to_string()],
+                removed_members: vec!["B".to_string(), "C".to_string()],
+            }]
+        );
+        assert_eq!(diff.added_rules, vec!["DOMAIN-SUFFIX,github.com,PROXY"]);
+        assert_eq!(diff.removed_rules, vec!["DOMAIN-SUFFIX,google.com,PROXY"]);
+        assert!(diff_configs(&new, &new).is_empty());
+
+        Ok(())
+    }
+}
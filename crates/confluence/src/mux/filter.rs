@@ -0,0 +1,233 @@
+use crate::clash::Proxy;
+use crate::error::ConfigError;
+use crate::models::subscribe_source::{self, ProxyMatcher, ProxyRenameRule};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+fn compile(config_name: &str, pattern: &str) -> Result<Regex, ConfigError> {
+    Regex::new(pattern).map_err(|source| ConfigError::ProxyFilterInvalid {
+        config_name: config_name.to_string(),
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+#[derive(Clone, Debug)]
+struct CompiledMatcher {
+    name: Option<Regex>,
+    kind: Option<Regex>,
+    server: Option<Regex>,
+}
+
+impl CompiledMatcher {
+    fn new(config_name: &str, matcher: &ProxyMatcher) -> Result<Option<Self>, ConfigError> {
+        let compile_some = |pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .filter(|p| !p.is_empty())
+                .map(|p| compile(config_name, p))
+                .transpose()
+        };
+        let matcher = Self {
+            name: compile_some(&matcher.name)?,
+            kind: compile_some(&matcher.kind)?,
+            server: compile_some(&matcher.server)?,
+        };
+        let is_empty = matcher.name.is_none() && matcher.kind.is_none() && matcher.server.is_none();
+        Ok((!is_empty).then_some(matcher))
+    }
+
+    fn is_match(&self, proxy: &Proxy) -> bool {
+        self.name.as_ref().is_none_or(|r| r.is_match(proxy.name()))
+            && self.kind.as_ref().is_none_or(|r| r.is_match(proxy.kind()))
+            && self
+                .server
+                .as_ref()
+                .is_none_or(|r| r.is_match(proxy.server()))
+    }
+}
+
+/// include / exclude matchers and rename rules of a subscribe source, applied to its
+/// proxies before they are muxed
+#[derive(Clone, Debug, Default)]
+pub struct ProxyFilter {
+    include: Option<CompiledMatcher>,
+    exclude: Option<CompiledMatcher>,
+    rename: Vec<(Regex, String)>,
+}
+
+impl ProxyFilter {
+    pub fn new(
+        config_name: &str,
+        include: Option<&ProxyMatcher>,
+        exclude: Option<&ProxyMatcher>,
+        rename: &[ProxyRenameRule],
+    ) -> Result<Self, ConfigError> {
+        Ok(Self {
+            include: include
+                .map(|m| CompiledMatcher::new(config_name, m))
+                .transpose()?
+                .flatten(),
+            exclude: exclude
+                .map(|m| CompiledMatcher::new(config_name, m))
+                .transpose()?
+                .flatten(),
+            rename: rename
+                .iter()
+                .map(|r| Ok((compile(config_name, &r.pattern)?, r.replacement.clone())))
+                .collect::<Result<_, ConfigError>>()?,
+        })
+    }
+
+    pub fn keeps(&self, proxy: &Proxy) -> bool {
+        self.include.as_ref().is_none_or(|m| m.is_match(proxy))
+            && !self.exclude.as_ref().is_some_and(|m| m.is_match(proxy))
+    }
+
+    /// a rename leaving nothing of the name keeps the original name
+    pub fn rename(&self, name: &str) -> String {
+        let renamed = self
+            .rename
+            .iter()
+            .fold(name.to_string(), |name, (pattern, replacement)| {
+                pattern.replace_all(&name, replacement).trim().to_string()
+            });
+        if renamed.is_empty() {
+            tracing::warn!("rename rules leave proxy {} without a name, keep it", name);
+            return name.to_string();
+        }
+        renamed
+    }
+
+    /// the kept proxies with rename rules applied, names taken by an earlier proxy
+    /// get a ` 2`, ` 3`, ... suffix, along with the final name of every kept original name
+    pub fn apply(&self, proxies: &[Proxy]) -> (Vec<Proxy>, HashMap<String, String>) {
+        let mut names = HashSet::new();
+        let mut final_names = HashMap::new();
+        let kept = proxies
+            .iter()
+            .filter(|p| self.keeps(p))
+            .map(|p| {
+                let mut p = p.clone();
+                let name = self.rename(p.name());
+                let mut unique_name = name.clone();
+                let mut n = 1;
+                while !names.insert(unique_name.clone()) {
+                    n += 1;
+                    unique_name = format!("{} {}", name, n);
+                }
+                final_names
+                    .entry(p.name().to_string())
+                    .or_insert_with(|| unique_name.clone());
+                p.set_name(unique_name);
+                p
+            })
+            .collect();
+        (kept, final_names)
+    }
+}
+
+impl TryFrom<&subscribe_source::Model> for ProxyFilter {
+    type Error = ConfigError;
+
+    fn try_from(value: &subscribe_source::Model) -> Result<Self, Self::Error> {
+        ProxyFilter::new(
+            &value.name,
+            value.proxy_include.as_ref(),
+            value.proxy_exclude.as_ref(),
+            value
+                .proxy_rename
+                .as_ref()
+                .map(|r| r.0.as_slice())
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clash::ClashConfig;
+    use std::assert_matches;
+
+    #[test]
+    fn test_proxy_filter() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let mut proxies = config.proxies;
+        proxies.push(serde_yaml::from_str(
+            "{ name: 'Expire: 2026-12-31', type: ss, server: 1.1.1.1, port: 1, cipher: aes-128-gcm, password: x }",
+        )?);
+
+        let filter = ProxyFilter::new(
+            "test",
+            Some(&ProxyMatcher {
+                kind: Some("^ss$".to_string()),
+                ..Default::default()
+            }),
+            Some(&ProxyMatcher {
+                name: Some("(?i)expire|traffic".to_string()),
+                ..Default::default()
+            }),
+            &[
+                ProxyRenameRule {
+                    pattern: "^(.*)$".to_string(),
+                    replacement: "🇯🇵 $1".to_string(),
+                },
+                ProxyRenameRule {
+                    pattern: r"\s*\[premium\]".to_string(),
+                    replacement: String::new(),
+                },
+            ],
+        )?;
+        let names = filter
+            .apply(&proxies)
+            .0
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["🇯🇵 A", "🇯🇵 B"]);
+        assert_eq!(filter.rename("Tokyo [premium]"), "🇯🇵 Tokyo");
+
+        let filter = ProxyFilter::new(
+            "test",
+            None,
+            None,
+            &[ProxyRenameRule {
+                pattern: r"^[AB]$|\s*\d+$".to_string(),
+                replacement: String::new(),
+            }],
+        )?;
+        assert_eq!(filter.rename("A"), "A");
+        let mut numbered = proxies.clone();
+        for (p, name) in numbered.iter_mut().zip(["HK 01", "HK 02", "HK 2"]) {
+            p.set_name(name.to_string());
+        }
+        let (renamed, final_names) = filter.apply(&numbered);
+        let names = renamed
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names[..3], ["HK", "HK 2", "HK 3"]);
+        assert_eq!(final_names["HK 02"], "HK 2");
+        assert_eq!(final_names["HK 2"], "HK 3");
+
+        let filter = ProxyFilter::new("test", Some(&ProxyMatcher::default()), None, &[])?;
+        assert_eq!(filter.apply(&proxies).0.len(), proxies.len());
+
+        assert_matches!(
+            ProxyFilter::new(
+                "test",
+                None,
+                Some(&ProxyMatcher {
+                    server: Some("(".to_string()),
+                    ..Default::default()
+                }),
+                &[],
+            ),
+            Err(ConfigError::ProxyFilterInvalid { .. })
+        );
+
+        Ok(())
+    }
+}
@@ -1,17 +1,207 @@
+pub mod diff;
+pub mod filter;
+pub mod region;
+pub mod revision;
+pub mod slot;
+pub mod validate;
+
+use serde_yaml::Value;
 use std::collections::{HashMap, HashSet};
 
-use crate::clash::utils::{parse_server_tld, ServerTld};
-use crate::clash::{ClashConfig, Proxy, ProxyGroup, ProxyGroupKind, Rule};
+use crate::clash::utils::{ServerTld, parse_server_tld};
+use crate::clash::{ClashConfig, IpCidr, Proxy, ProxyGroup, ProxyGroupKind, Rule, RuleCondition};
+use crate::error::ConfigError;
+use crate::models::confluence::{AutoGroupKind, SourceAutoGroup};
+use crate::models::subscribe_source;
+use filter::ProxyFilter;
+use region::{REGION_PATTERNS_KEY, REGIONS_SLOT, Regions, region_slot};
+use slot::MuxSlot;
+use validate::{BUILTIN_POLICIES, validate_config};
+
+/// keys of an imported source group which select members from outside its `proxies`,
+/// such as proxy providers or every proxy of the muxed config
+const IMPORT_STRIPPED_GROUP_KEYS: [&str; 4] =
+    ["use", "include-all", "include-all-proxies", "filter"];
+const DEFAULT_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+const DEFAULT_TEST_INTERVAL: u32 = 300;
+
+/// per source switches of the mux, stored on the subscribe source
+#[derive(Clone, Debug, Default)]
+pub struct MuxSourceOptions {
+    /// keep the proxy groups of the source, namespaced by the source name
+    pub import_proxy_groups: bool,
+    pub filter: ProxyFilter,
+    /// generate a `"{source} {kind}"` group over the source proxies
+    pub auto_group: Option<SourceAutoGroup>,
+    /// tags selecting the source in `<mux:tag=...>` slots
+    pub tags: Vec<String>,
+}
 
-const PROXY_SLOT: &str = "<mux>";
+impl TryFrom<&subscribe_source::Model> for MuxSourceOptions {
+    type Error = ConfigError;
+
+    fn try_from(value: &subscribe_source::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            import_proxy_groups: value.import_proxy_groups.unwrap_or_default(),
+            filter: value.try_into()?,
+            auto_group: None,
+            tags: value.tags.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MuxSource<'a> {
+    pub name: &'a str,
+    pub config: ClashConfig,
+    pub options: MuxSourceOptions,
+}
+
+impl<'a> MuxSource<'a> {
+    pub fn new(name: &'a str, config: ClashConfig) -> Self {
+        Self {
+            name,
+            config,
+            options: MuxSourceOptions::default(),
+        }
+    }
+}
 
+/// copy the proxy groups of a source as `"{source} {group}"`, with members pointing at
+/// the renamed proxies and groups, members dropped by the filter are removed and groups
+/// left without members are skipped
+fn import_source_groups(
+    source_name: &str,
+    groups: &[ProxyGroup],
+    filtered_names: &HashMap<String, String>,
+    renamed_proxies: &HashMap<String, String>,
+) -> Vec<ProxyGroup> {
+    let renamed_groups = groups
+        .iter()
+        .map(|g| (g.name.as_str(), format!("{} {}", source_name, g.name)))
+        .collect::<HashMap<_, _>>();
+
+    let mut imported = groups
+        .iter()
+        .map(|g| {
+            let proxies = g
+                .proxies
+                .iter()
+                .filter_map(|member| {
+                    let new_member = renamed_groups
+                        .get(member.as_str())
+                        .or_else(|| {
+                            filtered_names
+                                .get(member)
+                                .and_then(|name| renamed_proxies.get(name))
+                        })
+                        .cloned()
+                        .or_else(|| {
+                            BUILTIN_POLICIES
+                                .contains(&member.as_str())
+                                .then(|| member.clone())
+                        });
+                    if new_member.is_none() {
+                        tracing::warn!(
+                            "drop unknown member {} of proxy group {} from source {}",
+                            member,
+                            g.name,
+                            source_name
+                        );
+                    }
+                    new_member
+                })
+                .collect();
+            let mut others = g.others.clone();
+            for key in IMPORT_STRIPPED_GROUP_KEYS {
+                others.remove(key);
+            }
+            ProxyGroup {
+                name: renamed_groups[g.name.as_str()].clone(),
+                kind: g.kind.clone(),
+                others,
+                proxies,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let empty = imported
+        .iter()
+        .filter(|g| g.proxies.is_empty())
+        .map(|g| g.name.clone())
+        .collect::<HashSet<_>>();
+    imported.retain(|g| !empty.contains(&g.name));
+    for g in &mut imported {
+        g.proxies.retain(|p| !empty.contains(p));
+    }
+    imported
+}
+
+/// a url-test / fallback / load-balance group over all proxies of a source,
+/// none for a source without proxies
+fn source_auto_group(
+    source_name: &str,
+    auto_group: &SourceAutoGroup,
+    proxies: &[Proxy],
+) -> Option<ProxyGroup> {
+    if proxies.is_empty() {
+        return None;
+    }
+    let kind = match auto_group.kind {
+        AutoGroupKind::UrlTest => ProxyGroupKind::UrlTest,
+        AutoGroupKind::Fallback => ProxyGroupKind::Fallback,
+        AutoGroupKind::LoadBalance => ProxyGroupKind::LoadBalance,
+    };
+    let mut others = HashMap::new();
+    others.insert(
+        "url".to_string(),
+        Value::from(auto_group.url.as_deref().unwrap_or(DEFAULT_TEST_URL)),
+    );
+    others.insert(
+        "interval".to_string(),
+        Value::from(auto_group.interval.unwrap_or(DEFAULT_TEST_INTERVAL)),
+    );
+    if let (ProxyGroupKind::UrlTest, Some(tolerance)) = (&kind, auto_group.tolerance) {
+        others.insert("tolerance".to_string(), Value::from(tolerance));
+    }
+    Some(ProxyGroup {
+        name: format!("{} {}", source_name, kind.name()),
+        kind,
+        others,
+        proxies: proxies.iter().map(|p| p.name().to_string()).collect(),
+    })
+}
+
+/// mux a source on its own under an empty template, so a source which parses but can
+/// not be muxed is caught before it fails the mux of every other source
+pub fn check_mux_source(source: &MuxSource<'_>) -> Result<(), ConfigError> {
+    mux_configs(
+        source.name,
+        &ClashConfig::default(),
+        std::slice::from_ref(source),
+        &[],
+    )
+    .map(|_| ())
+    .map_err(|e| {
+        e.downcast::<ConfigError>()
+            .unwrap_or_else(ConfigError::Other)
+    })
+}
+
+/// `skipped_sources` are left out of a degraded mux, slots naming them resolve without them
 pub fn mux_configs(
     template_name: &str,
     template: &ClashConfig,
-    sources: &[(&str, ClashConfig)],
+    sources: &[MuxSource<'_>],
+    skipped_sources: &[&str],
 ) -> anyhow::Result<ClashConfig> {
-    let others = &template.others;
+    let mut others = template.others.clone();
+    others.remove(REGION_PATTERNS_KEY);
+    let regions = Regions::from_template(template)?;
     let rules = &template.rules;
+    for rule in rules {
+        rule.check()?;
+    }
     let proxy_groups = &template.proxy_groups;
     let template_proxies = &template.proxies;
     let mut source_name_to_proxies_map = HashMap::<&str, Vec<Proxy>>::new();
@@ -22,34 +212,39 @@ pub fn mux_configs(
     let mut mux_rules = vec![];
 
     {
-      for p in template_proxies {
-        {
-          // resolve proxy server root domain
-          let proxy_server_root = parse_server_tld(template_name, p.server())?;
-          proxy_servers_root_ltd.insert(proxy_server_root);
-        }
+        for p in template_proxies {
+            {
+                // resolve proxy server root domain
+                let proxy_server_root = parse_server_tld(template_name, p.server())?;
+                proxy_servers_root_ltd.insert(proxy_server_root);
+            }
 
-        {
-          // group proxies by config name
-          source_name_to_proxies_map
-            .entry(template_name)
-            .and_modify(|v| v.push(p.clone()))
-            .or_insert_with(|| vec![p.clone()]);
-        }
+            {
+                // group proxies by config name
+                source_name_to_proxies_map
+                    .entry(template_name)
+                    .and_modify(|v| v.push(p.clone()))
+                    .or_insert_with(|| vec![p.clone()]);
+            }
 
-        {
-          proxy_name_count
-            .entry(p.name())
-            .and_modify(|v| {
-              *v += 1;
-            })
-            .or_insert_with(|| 1);
+            {
+                proxy_name_count
+                    .entry(p.name())
+                    .and_modify(|v| {
+                        *v += 1;
+                    })
+                    .or_insert_with(|| 1);
+            }
         }
-      }
     }
 
-    for (source_name, source_config) in sources {
-        for p in &source_config.proxies {
+    let (source_proxies, source_filtered_names): (Vec<_>, Vec<_>) = sources
+        .iter()
+        .map(|s| s.options.filter.apply(&s.config.proxies))
+        .unzip();
+    for (source, proxies) in sources.iter().zip(&source_proxies) {
+        let source_name = source.name;
+        for p in proxies {
             {
                 // resolve proxy server root domain
                 let proxy_server_root = parse_server_tld(source_name, p.server())?;
@@ -76,19 +271,16 @@ pub fn mux_configs(
     }
 
     let mut mux_proxies = vec![];
+    let mut source_proxy_groups = vec![];
+    let mut source_proxy_names = HashMap::<&str, Vec<String>>::new();
 
     {
-        let source_names = sources.iter().map(|e| e.0.to_string()).collect::<Vec<_>>();
-
-        for g in proxy_groups {
-            let mut n = g.clone();
-            if let Some(index) = n.proxies.iter().position(|f| f.trim() == PROXY_SLOT) {
-                n.proxies.splice(index..index + 1, source_names.clone());
-            }
-            mux_proxy_groups.push(n);
-        }
-
-        for (source_name, proxies) in source_name_to_proxies_map {
+        let source_order = std::iter::once(template_name).chain(sources.iter().map(|s| s.name));
+        for source_name in source_order {
+            let Some(proxies) = source_name_to_proxies_map.remove(source_name) else {
+                continue;
+            };
+            let mut renamed_proxies = HashMap::<String, String>::new();
             let new_proxies = proxies
                 .into_iter()
                 .map(|mut p| {
@@ -99,52 +291,188 @@ pub fn mux_configs(
                     } else {
                         proxy_name.to_string()
                     };
+                    renamed_proxies.insert(proxy_name.to_string(), new_proxy_name.clone());
                     p.set_name(new_proxy_name);
                     p
                 })
                 .collect::<Vec<Proxy>>();
 
-            mux_proxy_groups.push(ProxyGroup {
+            let source_index = sources.iter().position(|s| s.name == source_name);
+            let source = source_index.map(|i| &sources[i]);
+            let mut imported_groups = vec![];
+            if let Some(auto_group) = source.and_then(|s| s.options.auto_group.as_ref()) {
+                imported_groups.extend(source_auto_group(source_name, auto_group, &new_proxies));
+            }
+            if let Some(i) = source_index.filter(|&i| sources[i].options.import_proxy_groups) {
+                imported_groups.extend(import_source_groups(
+                    source_name,
+                    &sources[i].config.proxy_groups,
+                    &source_filtered_names[i],
+                    &renamed_proxies,
+                ));
+            }
+
+            source_proxy_groups.push(ProxyGroup {
                 name: source_name.to_string(),
                 kind: ProxyGroupKind::Select,
-                proxies: new_proxies.iter().map(|s| s.name().to_string()).collect(),
+                proxies: imported_groups
+                    .iter()
+                    .map(|g| g.name.clone())
+                    .chain(new_proxies.iter().map(|s| s.name().to_string()))
+                    .collect(),
                 others: HashMap::new(),
             });
+            source_proxy_groups.extend(imported_groups);
 
+            if source.is_some() {
+                source_proxy_names.insert(
+                    source_name,
+                    new_proxies.iter().map(|p| p.name().to_string()).collect(),
+                );
+            }
             mux_proxies.extend(new_proxies);
         }
     }
 
     {
-        mux_rules.extend(proxy_servers_root_ltd.into_iter().map(|s| {
-            Rule({
-                match s {
-                    ServerTld::Tld(domain) => format!("DOMAIN-SUFFIX,{},DIRECT", domain),
-                    ServerTld::Ip(ip) => {
-                        if ip.is_ipv6() {
-                            format!("IP-CIDR6,{}/128,DIRECT", ip)
+        let taken_names = mux_proxies
+            .iter()
+            .map(|p| p.name())
+            .chain(source_proxy_groups.iter().map(|g| g.name.as_str()))
+            .collect::<HashSet<_>>();
+        let region_groups = regions.region_groups(&mux_proxies, &taken_names);
+        let mut regions_used = false;
+
+        for g in proxy_groups {
+            let mut n = g.clone();
+            let mut members = vec![];
+            // slots which may resolve to nothing fall back to DIRECT
+            let mut fallback_to_direct = false;
+            for member in n.proxies {
+                if let Some(slot) = MuxSlot::parse(&member) {
+                    for skipped in slot.sources.iter().filter(|s| skipped_sources.contains(s)) {
+                        tracing::warn!(
+                            "source {} of slot {} in proxy group {} is skipped",
+                            skipped,
+                            member.trim(),
+                            n.name
+                        );
+                        fallback_to_direct = true;
+                    }
+                    // skipped sources may have been the only ones selected by tags or by `<mux>`
+                    if slot.sources.is_empty() && !skipped_sources.is_empty() {
+                        fallback_to_direct = true;
+                    }
+                    if let Some(unknown) = slot.sources.iter().find(|name| {
+                        !sources.iter().any(|s| s.name == **name) && !skipped_sources.contains(name)
+                    }) {
+                        return Err(ConfigError::MuxSlotInvalid {
+                            slot: member.trim().to_string(),
+                            reason: format!(
+                                "unknown source {} used by proxy group {}",
+                                unknown, n.name
+                            ),
+                        }
+                        .into());
+                    }
+                    // sources without any proxy left have no group to refer to
+                    for source in sources.iter().filter(|s| slot.selects(s)) {
+                        let Some(proxy_names) = source_proxy_names.get(source.name) else {
+                            continue;
+                        };
+                        if slot.proxies {
+                            members.extend(proxy_names.iter().cloned());
                         } else {
-                            format!("IP-CIDR,{}/32,DIRECT", ip)
+                            members.push(source.name.to_string());
                         }
                     }
+                } else if member.trim() == REGIONS_SLOT {
+                    regions_used = true;
+                    fallback_to_direct = true;
+                    members.extend(region_groups.iter().map(|g| g.name.clone()));
+                } else if let Some(code) = region_slot(&member) {
+                    let proxies =
+                        regions
+                            .matching_proxies(code, &mux_proxies)
+                            .ok_or_else(|| ConfigError::RegionInvalid {
+                                region: code.to_string(),
+                                reason: format!("unknown region used by proxy group {}", n.name),
+                            })?;
+                    fallback_to_direct = true;
+                    members.extend(proxies);
+                } else {
+                    members.push(member);
                 }
-            })
+            }
+            // slots may overlap, keep the first occurrence of every member
+            let mut seen = HashSet::new();
+            members.retain(|m| seen.insert(m.clone()));
+            if members.is_empty() && fallback_to_direct {
+                tracing::warn!(
+                    "proxy group {} has no member left, fall back to DIRECT",
+                    n.name
+                );
+                members.push(String::from("DIRECT"));
+            }
+            n.proxies = members;
+            mux_proxy_groups.push(n);
+        }
+
+        if regions_used {
+            // template groups win over generated region groups of the same name
+            mux_proxy_groups.extend(
+                region_groups
+                    .into_iter()
+                    .filter(|g| !proxy_groups.iter().any(|t| t.name == g.name)),
+            );
+        }
+        mux_proxy_groups.extend(source_proxy_groups);
+    }
+
+    {
+        mux_rules.extend(proxy_servers_root_ltd.into_iter().map(|s| {
+            let condition = match s {
+                ServerTld::Tld(domain) => RuleCondition::DomainSuffix(domain.to_string()),
+                ServerTld::Ip(ip) if ip.is_ipv6() => RuleCondition::IpCidr6(IpCidr::host(ip)),
+                ServerTld::Ip(ip) => RuleCondition::IpCidr(IpCidr::host(ip)),
+            };
+            Rule::new(condition, "DIRECT")
         }));
         mux_rules.extend_from_slice(rules);
     }
 
-    Ok(ClashConfig {
-        others: others.clone(),
+    let mux_config = ClashConfig {
+        others,
         proxies: mux_proxies,
         proxy_groups: mux_proxy_groups,
         rules: mux_rules,
-    })
+    };
+
+    let report = validate_config(&mux_config);
+    for issue in report.issues.iter().filter(|i| !i.is_fatal()) {
+        tracing::warn!("muxed config {}: {}", template_name, issue);
+    }
+    if !report.is_valid() {
+        return Err(ConfigError::MuxInvalid {
+            config_name: template_name.to_string(),
+            report,
+        }
+        .into());
+    }
+
+    Ok(mux_config)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::clash::ClashConfig;
-    use crate::mux::mux_configs;
+    use crate::clash::{ClashConfig, ProxyGroupKind};
+    use crate::error::ConfigError;
+    use crate::models::confluence::{AutoGroupKind, SourceAutoGroup};
+    use crate::models::subscribe_source::{ProxyMatcher, ProxyRenameRule};
+    use crate::mux::filter::ProxyFilter;
+    use crate::mux::validate::MuxIssue;
+    use crate::mux::{IMPORT_STRIPPED_GROUP_KEYS, MuxSource, check_mux_source, mux_configs};
+    use std::assert_matches;
 
     #[test]
     fn test_mux_configs() -> anyhow::Result<()> {
@@ -157,9 +485,12 @@ mod tests {
         let config1: ClashConfig = serde_yaml::from_str(rules1)?;
         let config2: ClashConfig = serde_yaml::from_str(rules2)?;
         let config_tmpl: ClashConfig = serde_yaml::from_str(tmpl)?;
-        let sources = vec![("proxy1", config1), ("proxy2", config2)];
+        let sources = vec![
+            MuxSource::new("proxy1", config1),
+            MuxSource::new("proxy2", config2),
+        ];
 
-        let config_res = mux_configs("test", &config_tmpl, &sources)?;
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
 
         //         let expected_rules: Vec<Rule> = serde_yaml::from_str(
         //             r"
@@ -193,4 +524,351 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_mux_configs_dangling_reference() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let mut config_tmpl: ClashConfig =
+            serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        config_tmpl.rules.push("MATCH,Missing".parse()?);
+        let sources = vec![MuxSource::new("proxy1", config1)];
+
+        let err = mux_configs("test", &config_tmpl, &sources, &[]).unwrap_err();
+
+        assert_matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::MuxInvalid { report, .. })
+                if report.issues == vec![MuxIssue::DanglingRuleTarget {
+                    rule: "MATCH,Missing".to_string(),
+                    target: "Missing".to_string(),
+                }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_opaque_rules() -> anyhow::Result<()> {
+        let mut config1: ClashConfig =
+            serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        config1.rules = serde_yaml::from_str("['DOMAIN,broken']")?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let sources = vec![MuxSource::new("proxy1", config1)];
+        mux_configs("test", &config_tmpl, &sources, &[])?;
+
+        let mut broken_tmpl = config_tmpl.clone();
+        broken_tmpl.rules = serde_yaml::from_str("['DOMAIN,broken']")?;
+        let err = mux_configs("test", &broken_tmpl, &sources, &[]).unwrap_err();
+        assert_matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::RuleInvalid { .. })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_import_proxy_groups() -> anyhow::Result<()> {
+        let mut config1: ClashConfig =
+            serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        for (key, value) in [
+            ("use", "[provider]"),
+            ("include-all", "true"),
+            ("include-all-proxies", "true"),
+            ("filter", "HK"),
+        ] {
+            config1.proxy_groups[0]
+                .others
+                .insert(key.to_string(), serde_yaml::from_str(value)?);
+        }
+        let mut source1 = MuxSource::new("proxy1", config1.clone());
+        source1.options.import_proxy_groups = true;
+        // the same proxies in both sources get renamed
+        let sources = vec![source1, MuxSource::new("proxy2", config1)];
+
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        assert_eq!(
+            group("proxy1").unwrap().proxies,
+            vec!["proxy1 Proxy", "proxy1 A", "proxy1 B"]
+        );
+        assert_eq!(
+            group("proxy1 Proxy").unwrap().proxies,
+            vec!["DIRECT", "proxy1 A", "proxy1 B"]
+        );
+        assert!(group("proxy2 Proxy").is_none());
+        for key in IMPORT_STRIPPED_GROUP_KEYS {
+            assert!(!group("proxy1 Proxy").unwrap().others.contains_key(key));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_import_filtered_proxy_groups() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: "HK 01 | 1x", type: socks5, server: a.proxy1.com, port: 1 }
+  - { name: "HK 01 | 2x", type: socks5, server: b.proxy1.com, port: 1 }
+  - { name: "HK 02 | 1x", type: socks5, server: c.proxy1.com, port: 1 }
+  - { name: "HK 02", type: socks5, server: d.proxy1.com, port: 1 }
+proxy-groups:
+  - { name: "Fast", type: select, proxies: ["HK 01 | 2x"] }
+  - { name: "Cheap", type: select, proxies: ["HK 01 | 1x", "HK 02"] }
+rules: []
+            "#,
+        )?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let mut source1 = MuxSource::new("proxy1", config1);
+        source1.options.import_proxy_groups = true;
+        source1.options.filter = ProxyFilter::new(
+            "proxy1",
+            None,
+            Some(&ProxyMatcher {
+                name: Some("^HK 02 \\|".to_string()),
+                ..Default::default()
+            }),
+            &[ProxyRenameRule {
+                pattern: r"\s*\|.*$".to_string(),
+                replacement: String::new(),
+            }],
+        )?;
+        let sources = vec![source1];
+
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        // renamed to the same name, the second one got deduped
+        assert_eq!(group("proxy1 Fast").unwrap().proxies, vec!["HK 01 2"]);
+        // the excluded `HK 02 | 1x` renames to `HK 02` but is not that proxy
+        assert_eq!(
+            group("proxy1 Cheap").unwrap().proxies,
+            vec!["HK 01", "HK 02"]
+        );
+
+        let mut config2 = sources[0].config.clone();
+        config2.proxy_groups = serde_yaml::from_str(
+            r#"[{ name: "Excluded", type: select, proxies: ["HK 02 | 1x"] }]"#,
+        )?;
+        let mut source2 = MuxSource::new("proxy2", config2);
+        source2.options = sources[0].options.clone();
+        let config_res = mux_configs("test", &config_tmpl, &[source2], &[])?;
+        assert!(
+            !config_res
+                .proxy_groups
+                .iter()
+                .any(|g| g.name == "proxy2 Excluded")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_regions() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: "🇯🇵 Tokyo", type: socks5, server: a.proxy1.com, port: 1 }
+  - { name: "HK 01", type: socks5, server: b.proxy1.com, port: 1 }
+  - { name: "HK 02", type: socks5, server: c.proxy1.com, port: 1 }
+proxy-groups: []
+rules: []
+            "#,
+        )?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            r#"
+region-patterns:
+  HK: "^HK 01$"
+proxies: []
+proxy-groups:
+  - { name: PROXY, type: select, proxies: ["<regions>", "<mux>"] }
+  - { name: JP, type: url-test, proxies: ["<region:JP>"] }
+rules:
+  - MATCH,PROXY
+            "#,
+        )?;
+        let sources = vec![MuxSource::new("proxy1", config1)];
+
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        assert!(!config_res.others.contains_key("region-patterns"));
+        assert_eq!(group("PROXY").unwrap().proxies, vec!["HK", "JP", "proxy1"]);
+        assert_eq!(group("JP").unwrap().proxies, vec!["🇯🇵 Tokyo"]);
+        assert_eq!(group("HK").unwrap().proxies, vec!["HK 01"]);
+        assert_eq!(
+            config_res
+                .proxy_groups
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["PROXY", "JP", "HK", "proxy1"]
+        );
+
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies: []
+proxy-groups:
+  - { name: PROXY, type: select, proxies: ["<regions>", "<mux>"] }
+  - { name: KR, type: select, proxies: ["<region:KR>"] }
+rules:
+  - MATCH,PROXY
+            "#,
+        )?;
+        let sources = vec![MuxSource::new("HK", sources[0].config.clone())];
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        assert_eq!(
+            group("PROXY").unwrap().proxies,
+            vec!["Region HK", "JP", "HK"]
+        );
+        assert_eq!(group("Region HK").unwrap().proxies, vec!["HK 01", "HK 02"]);
+        assert_eq!(group("KR").unwrap().proxies, vec!["DIRECT"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_source_auto_group() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(include_str!("../tests/profile1.yaml"))?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(include_str!("../tests/tmpl.yaml"))?;
+        let mut source1 = MuxSource::new("proxy1", config1.clone());
+        source1.options.auto_group = Some(SourceAutoGroup {
+            kind: AutoGroupKind::UrlTest,
+            url: None,
+            interval: Some(600),
+            tolerance: Some(50),
+        });
+        // every proxy of the second source is filtered out
+        let mut source2 = MuxSource::new("proxy2", config1);
+        source2.options.auto_group = source1.options.auto_group.clone();
+        source2.options.filter = ProxyFilter::new(
+            "proxy2",
+            None,
+            Some(&ProxyMatcher {
+                name: Some(".*".to_string()),
+                ..Default::default()
+            }),
+            &[],
+        )?;
+        let sources = vec![source1, source2];
+
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        assert_eq!(
+            group("proxy1").unwrap().proxies,
+            vec!["proxy1 url-test", "A", "B"]
+        );
+        let auto_group = group("proxy1 url-test").unwrap();
+        assert_eq!(auto_group.kind, ProxyGroupKind::UrlTest);
+        assert_eq!(auto_group.proxies, vec!["A", "B"]);
+        assert_eq!(auto_group.others.get("interval"), Some(&600.into()));
+        assert_eq!(auto_group.others.get("tolerance"), Some(&50.into()));
+        assert_eq!(
+            auto_group.others.get("url"),
+            Some(&"https://www.gstatic.com/generate_204".into())
+        );
+        assert!(group("proxy2").is_none());
+        assert!(group("proxy2 url-test").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mux_configs_named_slots() -> anyhow::Result<()> {
+        let config1: ClashConfig = serde_yaml::from_str(
+            "{ proxies: [{ name: A, type: socks5, server: a.proxy1.com, port: 1 }], proxy-groups: [], rules: [] }",
+        )?;
+        let config2: ClashConfig = serde_yaml::from_str(
+            "{ proxies: [{ name: B, type: socks5, server: b.proxy2.com, port: 1 }], proxy-groups: [], rules: [] }",
+        )?;
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies: []
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [PREMIUM, "<mux:proxy1>", "<mux>"] }
+  - { name: PREMIUM, type: url-test, proxies: ["<mux:proxies,tag=premium>"] }
+rules:
+  - MATCH,PROXY
+            "#,
+        )?;
+        let source1 = MuxSource::new("proxy1", config1);
+        let mut source2 = MuxSource::new("proxy2", config2);
+        source2.options.tags = vec!["premium".to_string()];
+        let sources = vec![source1, source2];
+
+        let config_res = mux_configs("test", &config_tmpl, &sources, &[])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+
+        assert_eq!(
+            group("PROXY").unwrap().proxies,
+            vec!["PREMIUM", "proxy1", "proxy2"]
+        );
+        assert_eq!(group("PREMIUM").unwrap().proxies, vec!["B"]);
+
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            "{ proxies: [], proxy-groups: [{ name: PROXY, type: select, proxies: ['<mux:proxy3>'] }], rules: ['MATCH,PROXY'] }",
+        )?;
+        let err = mux_configs("test", &config_tmpl, &sources, &[]).unwrap_err();
+        assert_matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::MuxSlotInvalid { .. })
+        );
+
+        // a source skipped by a degraded mux is no unknown source
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies: []
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [SKIPPED, "<mux:proxy1,proxy3>"] }
+  - { name: SKIPPED, type: select, proxies: ["<mux:proxy3>"] }
+rules:
+  - MATCH,PROXY
+            "#,
+        )?;
+        let config_res = mux_configs("test", &config_tmpl, &sources, &["proxy3"])?;
+        let group = |name: &str| config_res.proxy_groups.iter().find(|g| g.name == name);
+        assert_eq!(group("PROXY").unwrap().proxies, vec!["SKIPPED", "proxy1"]);
+        assert_eq!(group("SKIPPED").unwrap().proxies, vec!["DIRECT"]);
+
+        // every source skipped, `<mux>` has nothing left
+        let config_tmpl: ClashConfig = serde_yaml::from_str(
+            "{ proxies: [], proxy-groups: [{ name: PROXY, type: select, proxies: ['<mux>'] }], rules: ['MATCH,PROXY'] }",
+        )?;
+        let config_res = mux_configs("test", &config_tmpl, &[], &["proxy1", "proxy2"])?;
+        assert_eq!(config_res.proxy_groups[0].proxies, vec!["DIRECT"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_mux_source() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(
+            "{ proxies: [{ name: A, type: socks5, server: a.proxy1.com, port: 1 }], proxy-groups: [], rules: [] }",
+        )?;
+        check_mux_source(&MuxSource::new("proxy1", config.clone()))?;
+
+        let mut broken_server = config.clone();
+        broken_server.proxies =
+            serde_yaml::from_str("[{ name: A, type: socks5, server: 'not a server', port: 1 }]")?;
+        assert!(check_mux_source(&MuxSource::new("proxy1", broken_server)).is_err());
+
+        let mut broken_group = config;
+        broken_group.proxy_groups = serde_yaml::from_str(
+            "[{ name: G, type: select, proxies: [A] }, { name: G, type: select, proxies: [A] }]",
+        )?;
+        let mut source = MuxSource::new("proxy1", broken_group);
+        source.options.import_proxy_groups = true;
+        assert_matches!(
+            check_mux_source(&source),
+            Err(ConfigError::MuxInvalid { .. })
+        );
+
+        Ok(())
+    }
 }
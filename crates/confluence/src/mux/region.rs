@@ -0,0 +1,264 @@
+use crate::clash::{ClashConfig, Proxy, ProxyGroup, ProxyGroupKind};
+use crate::error::ConfigError;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// template top-level key overriding or extending the built-in region patterns,
+/// a mapping of region code to a regex matched against proxy names
+pub const REGION_PATTERNS_KEY: &str = "region-patterns";
+/// template placeholder expanding to the proxies of one region, such as `<region:JP>`
+pub const REGION_SLOT_PREFIX: &str = "<region:";
+/// template placeholder expanding to the generated region groups
+pub const REGIONS_SLOT: &str = "<regions>";
+/// prefix of a generated region group whose code is already taken by a proxy or group
+const REGION_GROUP_PREFIX: &str = "Region";
+
+/// code, flag emoji, extra codes and names of the built-in regions
+const DEFAULT_REGIONS: [(&str, &str, &[&str], &[&str]); 16] = [
+    ("HK", "🇭🇰", &[], &["香港", "hong kong", "hongkong"]),
+    ("TW", "🇹🇼", &[], &["台湾", "臺灣", "taiwan", "taipei"]),
+    ("JP", "🇯🇵", &[], &["日本", "japan", "tokyo", "osaka"]),
+    ("KR", "🇰🇷", &[], &["韩国", "韓國", "korea", "seoul"]),
+    ("SG", "🇸🇬", &[], &["新加坡", "狮城", "singapore"]),
+    (
+        "US",
+        "🇺🇸",
+        &["USA"],
+        &[
+            "美国",
+            "美國",
+            "united states",
+            "america",
+            "los angeles",
+            "san jose",
+            "seattle",
+        ],
+    ),
+    (
+        "GB",
+        "🇬🇧",
+        &["UK"],
+        &["英国", "英國", "united kingdom", "britain", "london"],
+    ),
+    ("DE", "🇩🇪", &[], &["德国", "德國", "germany", "frankfurt"]),
+    ("FR", "🇫🇷", &[], &["法国", "法國", "france", "paris"]),
+    (
+        "NL",
+        "🇳🇱",
+        &[],
+        &["荷兰", "荷蘭", "netherlands", "amsterdam"],
+    ),
+    (
+        "CA",
+        "🇨🇦",
+        &[],
+        &["加拿大", "canada", "toronto", "vancouver"],
+    ),
+    (
+        "AU",
+        "🇦🇺",
+        &[],
+        &["澳大利亚", "澳洲", "australia", "sydney"],
+    ),
+    ("RU", "🇷🇺", &[], &["俄罗斯", "俄羅斯", "russia", "moscow"]),
+    ("IN", "🇮🇳", &[], &["印度", "india", "mumbai"]),
+    (
+        "TR",
+        "🇹🇷",
+        &[],
+        &["土耳其", "turkey", "türkiye", "istanbul"],
+    ),
+    ("AR", "🇦🇷", &[], &["阿根廷", "argentina"]),
+];
+
+/// region codes are matched case sensitive and not inside other latin words,
+/// so `HK01` and `[HK]` match but `Shkodra` does not
+fn default_pattern(code: &str, flag: &str, codes: &[&str], names: &[&str]) -> String {
+    let codes = std::iter::once(code)
+        .chain(codes.iter().copied())
+        .collect::<Vec<_>>()
+        .join("|");
+    let names = names
+        .iter()
+        .map(|n| regex::escape(n))
+        .collect::<Vec<_>>()
+        .join("|");
+    format!(
+        "(?:^|[^A-Za-z])(?:{})(?:[^A-Za-z]|$)|(?i:{})|{}",
+        codes, names, flag
+    )
+}
+
+#[derive(Clone, Debug)]
+pub struct Regions {
+    regions: Vec<(String, Regex)>,
+}
+
+impl Default for Regions {
+    fn default() -> Self {
+        let regions = DEFAULT_REGIONS
+            .iter()
+            .map(|(code, flag, codes, names)| {
+                let pattern = default_pattern(code, flag, codes, names);
+                (code.to_string(), Regex::new(&pattern).unwrap())
+            })
+            .collect();
+        Self { regions }
+    }
+}
+
+impl Regions {
+    /// the built-in regions with the patterns of the template `region-patterns` applied
+    pub fn from_template(template: &ClashConfig) -> Result<Self, ConfigError> {
+        let mut regions = Self::default();
+        let Some(patterns) = template.others.get(REGION_PATTERNS_KEY) else {
+            return Ok(regions);
+        };
+        let patterns = patterns
+            .as_mapping()
+            .ok_or_else(|| ConfigError::RegionInvalid {
+                region: REGION_PATTERNS_KEY.to_string(),
+                reason: String::from("expected a mapping of region code to pattern"),
+            })?;
+        for (code, pattern) in patterns {
+            let (Some(code), Some(pattern)) = (code.as_str(), pattern.as_str()) else {
+                return Err(ConfigError::RegionInvalid {
+                    region: format!("{:?}", code),
+                    reason: String::from("region code and pattern must be strings"),
+                });
+            };
+            let pattern = Regex::new(pattern).map_err(|e| ConfigError::RegionInvalid {
+                region: code.to_string(),
+                reason: e.to_string(),
+            })?;
+            match regions.regions.iter_mut().find(|(c, _)| c == code) {
+                Some(region) => region.1 = pattern,
+                None => regions.regions.push((code.to_string(), pattern)),
+            }
+        }
+        Ok(regions)
+    }
+
+    /// names of the proxies matching the region, in proxy order
+    pub fn matching_proxies(&self, code: &str, proxies: &[Proxy]) -> Option<Vec<String>> {
+        let (_, pattern) = self.regions.iter().find(|(c, _)| c == code)?;
+        Some(
+            proxies
+                .iter()
+                .filter(|p| pattern.is_match(p.name()))
+                .map(|p| p.name().to_string())
+                .collect(),
+        )
+    }
+
+    /// a select group named by the region code for every region with matching proxies,
+    /// a code taken by a proxy or group gets the `Region` prefix, or is skipped if that is taken too
+    pub fn region_groups(&self, proxies: &[Proxy], taken: &HashSet<&str>) -> Vec<ProxyGroup> {
+        self.regions
+            .iter()
+            .filter_map(|(code, _)| {
+                let members = self.matching_proxies(code, proxies)?;
+                if members.is_empty() {
+                    return None;
+                }
+                let name = [code.clone(), format!("{} {}", REGION_GROUP_PREFIX, code)]
+                    .into_iter()
+                    .find(|name| !taken.contains(name.as_str()));
+                if name.is_none() {
+                    tracing::warn!("skip region group {}, the name is already taken", code);
+                }
+                Some(ProxyGroup {
+                    name: name?,
+                    kind: ProxyGroupKind::Select,
+                    others: HashMap::new(),
+                    proxies: members,
+                })
+            })
+            .collect()
+    }
+}
+
+/// the region code of a `<region:XX>` placeholder
+pub fn region_slot(member: &str) -> Option<&str> {
+    member
+        .trim()
+        .strip_prefix(REGION_SLOT_PREFIX)?
+        .strip_suffix('>')
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::assert_matches;
+
+    #[test]
+    fn test_regions() -> anyhow::Result<()> {
+        let proxies: Vec<Proxy> = serde_yaml::from_str(
+            r#"
+- { name: "🇯🇵 Tokyo 01", type: socks5, server: a.example.com, port: 1 }
+- { name: "HK02", type: socks5, server: b.example.com, port: 1 }
+- { name: "香港 IPLC", type: socks5, server: c.example.com, port: 1 }
+- { name: "Shkodra", type: socks5, server: d.example.com, port: 1 }
+- { name: "[US] Seattle", type: socks5, server: e.example.com, port: 1 }
+- { name: "Mars 1", type: socks5, server: f.example.com, port: 1 }
+            "#,
+        )?;
+
+        let regions = Regions::default();
+        assert_eq!(
+            regions.matching_proxies("HK", &proxies),
+            Some(vec!["HK02".to_string(), "香港 IPLC".to_string()])
+        );
+        assert_eq!(regions.matching_proxies("XX", &proxies), None);
+        assert_eq!(
+            regions
+                .region_groups(&proxies, &HashSet::new())
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["HK", "JP", "US"]
+        );
+        assert_eq!(
+            regions
+                .region_groups(&proxies, &HashSet::from(["HK", "JP", "Region JP"]))
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Region HK", "US"]
+        );
+
+        let template: ClashConfig = serde_yaml::from_str(
+            r#"
+region-patterns:
+  JP: "Tokyo"
+  MARS: "(?i)mars"
+proxies: []
+proxy-groups: []
+rules: []
+            "#,
+        )?;
+        let regions = Regions::from_template(&template)?;
+        assert_eq!(
+            regions.matching_proxies("MARS", &proxies),
+            Some(vec!["Mars 1".to_string()])
+        );
+        assert_eq!(
+            regions.matching_proxies("JP", &proxies),
+            Some(vec!["🇯🇵 Tokyo 01".to_string()])
+        );
+
+        let template: ClashConfig = serde_yaml::from_str(
+            "{ region-patterns: { JP: '(' }, proxies: [], proxy-groups: [], rules: [] }",
+        )?;
+        assert_matches!(
+            Regions::from_template(&template),
+            Err(ConfigError::RegionInvalid { .. })
+        );
+
+        assert_eq!(region_slot("<region:JP>"), Some("JP"));
+        assert_eq!(region_slot("<mux>"), None);
+
+        Ok(())
+    }
+}
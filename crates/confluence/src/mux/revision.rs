@@ -0,0 +1,50 @@
+use crate::models::confluence_revision::DiffStats;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// hex sha256 identifying a mux content or a fetched payload
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// lines only present in one of the contents, counted with multiplicity and ignoring order
+pub fn diff_stats(old: &str, new: &str) -> DiffStats {
+    let mut counts = HashMap::<&str, i64>::new();
+    for line in old.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts
+        .values()
+        .fold(DiffStats::default(), |mut stats, count| {
+            if *count > 0 {
+                stats.added_lines += *count as u32;
+            } else {
+                stats.removed_lines += count.unsigned_abs() as u32;
+            }
+            stats
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revision_stats() {
+        assert_eq!(content_hash("a"), content_hash("a"));
+        assert_ne!(content_hash("a"), content_hash("b"));
+        assert_eq!(content_hash("").len(), 64);
+
+        assert_eq!(
+            diff_stats("a\nb\nb\nc\n", "b\nc\nd\ne\n"),
+            DiffStats {
+                added_lines: 2,
+                removed_lines: 2,
+            }
+        );
+        assert_eq!(diff_stats("a\nb\n", "a\nb\n"), DiffStats::default());
+    }
+}
@@ -0,0 +1,75 @@
+use super::MuxSource;
+
+/// `<mux>` expands to the groups of all sources
+pub const PROXY_SLOT: &str = "<mux>";
+const PROXY_SLOT_PREFIX: &str = "<mux:";
+const PROXIES_ARG: &str = "proxies";
+const TAG_ARG_PREFIX: &str = "tag=";
+
+/// a mux placeholder in a template group, `<mux:a,b>` selects sources by name,
+/// `<mux:tag=premium>` by tag and `<mux:proxies>` expands to the proxies instead of
+/// the source groups, arguments can be combined such as `<mux:proxies,tag=premium>`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MuxSlot<'a> {
+    pub proxies: bool,
+    pub sources: Vec<&'a str>,
+    pub tags: Vec<&'a str>,
+}
+
+impl<'a> MuxSlot<'a> {
+    pub fn parse(member: &'a str) -> Option<Self> {
+        let member = member.trim();
+        if member == PROXY_SLOT {
+            return Some(Self::default());
+        }
+        let args = member.strip_prefix(PROXY_SLOT_PREFIX)?.strip_suffix('>')?;
+        let mut slot = Self::default();
+        for arg in args.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            if arg == PROXIES_ARG {
+                slot.proxies = true;
+            } else if let Some(tag) = arg.strip_prefix(TAG_ARG_PREFIX) {
+                slot.tags.push(tag.trim());
+            } else {
+                slot.sources.push(arg);
+            }
+        }
+        Some(slot)
+    }
+
+    /// a slot without names or tags selects every source
+    pub fn selects(&self, source: &MuxSource<'_>) -> bool {
+        (self.sources.is_empty() && self.tags.is_empty())
+            || self.sources.contains(&source.name)
+            || self
+                .tags
+                .iter()
+                .any(|t| source.options.tags.iter().any(|s| s == t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mux_slot() {
+        assert_eq!(MuxSlot::parse(" <mux> "), Some(MuxSlot::default()));
+        assert_eq!(
+            MuxSlot::parse("<mux:a, b>"),
+            Some(MuxSlot {
+                sources: vec!["a", "b"],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            MuxSlot::parse("<mux:proxies,tag=premium>"),
+            Some(MuxSlot {
+                proxies: true,
+                tags: vec!["premium"],
+                ..Default::default()
+            })
+        );
+        assert_eq!(MuxSlot::parse("<region:JP>"), None);
+        assert_eq!(MuxSlot::parse("DIRECT"), None);
+    }
+}
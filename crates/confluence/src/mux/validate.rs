@@ -0,0 +1,296 @@
+use crate::clash::{ClashConfig, ProxyGroup, RuleCondition};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+/// policies clash provides without declaring them as proxies or groups
+pub const BUILTIN_POLICIES: [&str; 6] = [
+    "DIRECT",
+    "REJECT",
+    "REJECT-DROP",
+    "PASS",
+    "COMPATIBLE",
+    "GLOBAL",
+];
+
+/// keys of a proxy group adding members from outside its `proxies` list
+const GROUP_MEMBER_SOURCE_KEYS: [&str; 4] = [
+    "use",
+    "include-all",
+    "include-all-proxies",
+    "include-all-providers",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MuxIssue {
+    /// a proxy group member which is neither a proxy, a group nor a builtin policy
+    DanglingGroupMember { group_name: String, member: String },
+    /// a rule whose target policy does not exist
+    DanglingRuleTarget { rule: String, target: String },
+    /// a proxy group referenced by no rule and no other group
+    UnusedGroup { group_name: String },
+    /// proxy groups which contain each other, in reference order
+    GroupCycle { group_names: Vec<String> },
+    /// a name shared by several proxies or groups
+    DuplicateName { name: String },
+    /// a proxy group without any member
+    EmptyGroup { group_name: String },
+}
+
+impl MuxIssue {
+    /// whether clash refuses to load a config with this issue
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, MuxIssue::UnusedGroup { .. })
+    }
+}
+
+impl Display for MuxIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MuxIssue::DanglingGroupMember { group_name, member } => {
+                write!(f, "proxy group {} refers to unknown {}", group_name, member)
+            }
+            MuxIssue::DanglingRuleTarget { rule, target } => {
+                write!(f, "rule {} refers to unknown {}", rule, target)
+            }
+            MuxIssue::UnusedGroup { group_name } => {
+                write!(f, "proxy group {} is never used", group_name)
+            }
+            MuxIssue::GroupCycle { group_names } => {
+                write!(f, "proxy groups form a cycle {}", group_names.join(" -> "))
+            }
+            MuxIssue::DuplicateName { name } => {
+                write!(f, "name {} is used by more than one proxy or group", name)
+            }
+            MuxIssue::EmptyGroup { group_name } => {
+                write!(f, "proxy group {} has no member", group_name)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<MuxIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        !self.issues.iter().any(MuxIssue::is_fatal)
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let issues = self
+            .issues
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        f.write_str(&issues.join("; "))
+    }
+}
+
+/// check that names are unique, groups are not empty and every group member and rule target
+/// of a muxed config resolves, and report unused groups and cycles between groups
+pub fn validate_config(config: &ClashConfig) -> ValidationReport {
+    let mut issues = vec![];
+    let mut names = HashSet::new();
+    let mut duplicate_names = HashSet::new();
+    for name in config
+        .proxies
+        .iter()
+        .map(|p| p.name())
+        .chain(config.proxy_groups.iter().map(|g| g.name.as_str()))
+    {
+        if !names.insert(name) && duplicate_names.insert(name) {
+            issues.push(MuxIssue::DuplicateName {
+                name: name.to_string(),
+            });
+        }
+    }
+    let proxy_names = config
+        .proxies
+        .iter()
+        .map(|p| p.name())
+        .collect::<HashSet<_>>();
+    let groups = config
+        .proxy_groups
+        .iter()
+        .map(|g| (g.name.as_str(), g))
+        .collect::<HashMap<_, _>>();
+    let resolves = |name: &str| {
+        proxy_names.contains(name) || groups.contains_key(name) || BUILTIN_POLICIES.contains(&name)
+    };
+    let mut used_groups = HashSet::<&str>::new();
+
+    for group in &config.proxy_groups {
+        if group.proxies.is_empty()
+            && !GROUP_MEMBER_SOURCE_KEYS
+                .iter()
+                .any(|k| group.others.contains_key(*k))
+        {
+            issues.push(MuxIssue::EmptyGroup {
+                group_name: group.name.clone(),
+            });
+        }
+        for member in &group.proxies {
+            if groups.contains_key(member.as_str()) {
+                used_groups.insert(member);
+            } else if !resolves(member) {
+                issues.push(MuxIssue::DanglingGroupMember {
+                    group_name: group.name.clone(),
+                    member: member.clone(),
+                });
+            }
+        }
+    }
+
+    for rule in &config.rules {
+        // sub rules target a rule set, not a policy
+        if matches!(&rule.condition, RuleCondition::Other { kind, .. } if kind == "SUB-RULE") {
+            continue;
+        }
+        if groups.contains_key(rule.target.as_str()) {
+            used_groups.insert(&rule.target);
+        } else if !resolves(&rule.target) {
+            issues.push(MuxIssue::DanglingRuleTarget {
+                rule: rule.to_string(),
+                target: rule.target.clone(),
+            });
+        }
+    }
+
+    for group in &config.proxy_groups {
+        if !used_groups.contains(group.name.as_str()) && group.name != "GLOBAL" {
+            issues.push(MuxIssue::UnusedGroup {
+                group_name: group.name.clone(),
+            });
+        }
+    }
+
+    // depth first search over group members, a member already on the path closes a cycle
+    let mut visited = HashSet::<&str>::new();
+    for group in &config.proxy_groups {
+        let mut path = vec![];
+        find_cycles(&group.name, &groups, &mut visited, &mut path, &mut issues);
+    }
+
+    ValidationReport { issues }
+}
+
+fn find_cycles<'a>(
+    name: &'a str,
+    groups: &HashMap<&'a str, &'a ProxyGroup>,
+    visited: &mut HashSet<&'a str>,
+    path: &mut Vec<&'a str>,
+    issues: &mut Vec<MuxIssue>,
+) {
+    if let Some(index) = path.iter().position(|p| *p == name) {
+        let mut group_names = path[index..]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        group_names.push(name.to_string());
+        issues.push(MuxIssue::GroupCycle { group_names });
+        return;
+    }
+    if !visited.insert(name) {
+        return;
+    }
+    let Some(group) = groups.get(name) else {
+        return;
+    };
+    path.push(name);
+    for member in &group.proxies {
+        if groups.contains_key(member.as_str()) {
+            find_cycles(member, groups, visited, path, issues);
+        }
+    }
+    path.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_config() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: A, type: ss, server: a.example.com, port: 443, cipher: aes-128-gcm, password: x }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [AUTO, A, DIRECT, B] }
+  - { name: AUTO, type: select, proxies: [LOOP] }
+  - { name: LOOP, type: select, proxies: [AUTO] }
+  - { name: SPARE, type: select, proxies: [A] }
+rules:
+  - DOMAIN-SUFFIX,google.com,PROXY
+  - MATCH,Proxy
+            "#,
+        )?;
+
+        let report = validate_config(&config);
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.issues,
+            vec![
+                MuxIssue::DanglingGroupMember {
+                    group_name: "PROXY".to_string(),
+                    member: "B".to_string(),
+                },
+                MuxIssue::DanglingRuleTarget {
+                    rule: "MATCH,Proxy".to_string(),
+                    target: "Proxy".to_string(),
+                },
+                MuxIssue::UnusedGroup {
+                    group_name: "SPARE".to_string(),
+                },
+                MuxIssue::GroupCycle {
+                    group_names: vec!["AUTO".to_string(), "LOOP".to_string(), "AUTO".to_string()],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_config_names() -> anyhow::Result<()> {
+        let config: ClashConfig = serde_yaml::from_str(
+            r#"
+proxies:
+  - { name: A, type: socks5, server: a.example.com, port: 1 }
+  - { name: A, type: socks5, server: b.example.com, port: 1 }
+proxy-groups:
+  - { name: PROXY, type: select, proxies: [A, EMPTY, PROVIDED] }
+  - { name: EMPTY, type: select, proxies: [] }
+  - { name: PROVIDED, type: select, use: [provider], proxies: [] }
+  - { name: PROXY, type: select, proxies: [DIRECT] }
+rules:
+  - MATCH,PROXY
+            "#,
+        )?;
+
+        let report = validate_config(&config);
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.issues,
+            vec![
+                MuxIssue::DuplicateName {
+                    name: "A".to_string(),
+                },
+                MuxIssue::DuplicateName {
+                    name: "PROXY".to_string(),
+                },
+                MuxIssue::EmptyGroup {
+                    group_name: "EMPTY".to_string(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -1,16 +1,35 @@
 use crate::auth::CurrentUser;
 use crate::clash::http::{
-    SUBSCRIPTION_USERINFO_HEADER, SUB_DOWNLOAD, SUB_EXPIRE, SUB_TOTAL, SUB_UPLOAD,
+    SUB_DOWNLOAD, SUB_EXPIRE, SUB_TOTAL, SUB_UPLOAD, SUBSCRIPTION_USERINFO_HEADER,
 };
-use crate::clash::{parse_subscription_userinfo_in_header, ClashConfig};
-use crate::config::AppConfig;
+use crate::clash::{
+    ClashConfig, parse_subscription_content, parse_subscription_userinfo_in_header,
+};
+use crate::config::{AppConfig, FetchConfig};
+use crate::convert::render_profile;
+use crate::crypto::{MASK, SecretCipher};
 use crate::dto::{
-    ConfluenceUpdateCronDto, SubscribeSourceCreationDto, SubscribeSourceDto,
-    SubscribeSourceUpdateDto,
+    ConfluenceRevisionDto, ConfluenceUpdateCronDto, ProfileTokenQueryDto, ProfileUpdateDto,
+    ProxyTestDto, ProxyTestResultDto, RevisionDiffQueryDto, SubscribeSourceCreationDto,
+    SubscribeSourceDto, SubscribeSourceQueryDto, SubscribeSourceSnapshotDto,
+    SubscribeSourceSyncResultDto, SubscribeSourceSyncStatus, SubscribeSourceUpdateDto,
+    TemplateCreationDto, TemplateDto, TemplateUpdateDto, TemplateVersionDto,
 };
 use crate::error::ConfigError;
-use crate::models::subscribe_source;
-use crate::mux::mux_configs;
+use crate::fetch::{
+    ClientKey, Fetcher, ProxySettings, check_subscription_response, fetch_with_retry,
+    parse_request_headers,
+};
+use crate::models::confluence::{MuxFailure, MuxFailures};
+use crate::models::confluence_revision::{self, MuxDiff, RevisionSource, RevisionSources};
+use crate::models::subscribe_source::{
+    self, FetchOverrides, ProxyMatcher, ProxyRenameRules, RequestHeaders,
+};
+use crate::models::{subscribe_source_snapshot, template, template_version};
+use crate::mux::diff::diff_configs;
+use crate::mux::filter::ProxyFilter;
+use crate::mux::revision::{content_hash, diff_stats};
+use crate::mux::{MuxSource, MuxSourceOptions, check_mux_source, mux_configs};
 use crate::{
     dto::ProfileCreationDto,
     error::AppError,
@@ -21,41 +40,59 @@ use crate::{
         models::confluence,
     },
 };
-use axum::extract::{Path, State};
-use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header};
 use axum::{Extension, Json};
+use chrono::Utc;
 use chrono_tz::Tz;
 use cron::Schedule;
-use futures::future::try_join_all;
+use futures::{StreamExt, stream};
 use itertools::izip;
+use sea_orm::ActiveValue::{Set, Unchanged};
 use sea_orm::prelude::*;
-use sea_orm::ActiveValue::Set;
-use sea_orm::{IntoActiveModel, QuerySelect, TryIntoModel};
+use sea_orm::{
+    Condition, ConnectionTrait, FromQueryResult, IntoActiveModel, QueryOrder, QuerySelect,
+    TransactionTrait, TryIntoModel,
+};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// answers an empty 204 to anyone, which makes it cheap to reach through a proxy
+const PROXY_TEST_URL: &str = "https://www.gstatic.com/generate_204";
+/// fetched payloads kept per subscribe source
+const SNAPSHOT_HISTORY_LIMIT: u64 = 10;
+/// mux revisions kept per confluence, besides the ones pinned by a profile
+const REVISION_HISTORY_LIMIT: u64 = 20;
+/// subscribe sources of a confluence fetched at once
+const SYNC_CONCURRENCY: usize = 4;
+/// subscribe sources read and written at once when resealing secrets
+const RESEAL_BATCH_SIZE: u64 = 100;
+
 #[derive(Clone)]
 pub struct JwksConfig {
     pub jwks_set: Arc<biscuit::jwk::JWKSet<biscuit::Empty>>,
     pub jwks_expiry: std::time::Instant,
 }
 
-#[derive(Clone)]
 pub struct AppState {
     pub conn: DatabaseConnection,
     pub config: AppConfig,
     pub names_generator: Arc<rnglib::RNG>,
     pub jwks: Arc<RwLock<Option<JwksConfig>>>,
+    pub fetcher: Arc<Fetcher>,
+    pub cipher: Arc<SecretCipher>,
 }
 
 impl AppState {
     pub fn new(conn: DatabaseConnection, config: AppConfig) -> Self {
         Self {
             conn,
-            config,
             names_generator: Arc::new(rnglib::RNG::from(&rnglib::Language::Elven)),
             jwks: Arc::new(RwLock::new(None)),
+            fetcher: Arc::new(Fetcher::new(config.fetch.clone())),
+            cipher: Arc::new(SecretCipher::new(config.secret_keys.clone())),
+            config,
         }
     }
 }
@@ -72,6 +109,152 @@ pub async fn find_one_confluence_in_db(
         .ok_or_else(|| AppError::DbNotFound(format!("cannot find post id = {} for you", id)))
 }
 
+pub async fn find_one_subscribe_source_in_db(
+    db: &DatabaseConnection,
+    id: i32,
+    current_user: &CurrentUser,
+) -> Result<subscribe_source::Model, AppError> {
+    subscribe_source::Entity::find_by_id(id)
+        .inner_join(confluence::Entity)
+        .filter(confluence::Column::Creator.eq(&current_user.user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::DbNotFound(format!("cannot find subscribe source id = {}", id)))
+}
+
+pub async fn find_one_visible_template_in_db(
+    db: &DatabaseConnection,
+    id: i32,
+    current_user: &CurrentUser,
+) -> Result<template::Model, AppError> {
+    template::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .filter(|t| t.is_visible_to(&current_user.user_id))
+        .ok_or_else(|| AppError::DbNotFound(format!("cannot find template id = {} for you", id)))
+}
+
+pub async fn find_one_owned_template_in_db(
+    db: &DatabaseConnection,
+    id: i32,
+    current_user: &CurrentUser,
+) -> Result<template::Model, AppError> {
+    template::Entity::find_by_id(id)
+        .filter(template::Column::Creator.eq(&current_user.user_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::DbNotFound(format!("cannot find template id = {} for you", id)))
+}
+
+/// the template content a confluence is muxed with, the library template it follows if it is
+/// still visible to the confluence creator, otherwise the inline template
+pub async fn find_confluence_template_content(
+    db: &DatabaseConnection,
+    cm: &confluence::Model,
+) -> Result<String, AppError> {
+    let Some(template_id) = cm.template_id else {
+        return Ok(cm.template.clone());
+    };
+    let tm = template::Entity::find_by_id(template_id)
+        .one(db)
+        .await?
+        .filter(|t| t.is_visible_to(&cm.creator));
+    match tm {
+        Some(tm) => Ok(tm.content),
+        None => {
+            tracing::warn!(
+                "template {} is not visible to confluence {}, mux with its inline template",
+                template_id,
+                cm.id
+            );
+            Ok(cm.template.clone())
+        }
+    }
+}
+
+pub async fn find_one_confluence_revision_in_db(
+    db: &DatabaseConnection,
+    confluence_id: i32,
+    revision_id: i32,
+) -> Result<confluence_revision::Model, AppError> {
+    confluence_revision::Entity::find_by_id(revision_id)
+        .filter(confluence_revision::Column::ConfluenceId.eq(confluence_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::DbNotFound(format!(
+                "cannot find revision id = {} of confluence id = {}",
+                revision_id, confluence_id
+            ))
+        })
+}
+
+/// the structured diff of two mux contents, none if either is no clash config
+fn diff_mux_contents(old: &str, new: &str) -> Option<MuxDiff> {
+    let old = serde_yaml::from_str::<ClashConfig>(old).ok()?;
+    let new = serde_yaml::from_str::<ClashConfig>(new).ok()?;
+    Some(diff_configs(&old, &new))
+}
+
+/// store the mux content as a new revision, unless it is the content of the latest revision,
+/// and drop the revisions past the history limit which no profile is pinned to
+pub async fn record_confluence_revision<C: ConnectionTrait>(
+    db: &C,
+    confluence_id: i32,
+    content: &str,
+    sources: RevisionSources,
+) -> Result<confluence_revision::Model, AppError> {
+    let content_hash = content_hash(content);
+    let latest = confluence_revision::Entity::find()
+        .filter(confluence_revision::Column::ConfluenceId.eq(confluence_id))
+        .order_by_desc(confluence_revision::Column::Id)
+        .one(db)
+        .await?;
+    if let Some(latest) = latest.as_ref()
+        && latest.content_hash == content_hash
+    {
+        return Ok(latest.clone());
+    }
+    let rm = confluence_revision::ActiveModel {
+        confluence_id: Set(confluence_id),
+        content: Set(content.to_string()),
+        content_hash: Set(content_hash),
+        sources: Set(sources),
+        diff_stats: Set(latest.as_ref().map(|r| diff_stats(&r.content, content))),
+        diff: Set(latest.and_then(|r| diff_mux_contents(&r.content, content))),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+    let pinned_revision_ids = profile::Entity::find()
+        .select_only()
+        .column(profile::Column::RevisionId)
+        .filter(profile::Column::ConfluenceId.eq(confluence_id))
+        .filter(profile::Column::RevisionId.is_not_null())
+        .into_tuple::<i32>()
+        .all(db)
+        .await?;
+    let stale_revision_ids = confluence_revision::Entity::find()
+        .select_only()
+        .column(confluence_revision::Column::Id)
+        .filter(confluence_revision::Column::ConfluenceId.eq(confluence_id))
+        .order_by_desc(confluence_revision::Column::Id)
+        .offset(REVISION_HISTORY_LIMIT)
+        .into_tuple::<i32>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|revision_id| !pinned_revision_ids.contains(revision_id))
+        .collect::<Vec<_>>();
+    if !stale_revision_ids.is_empty() {
+        confluence_revision::Entity::delete_many()
+            .filter(confluence_revision::Column::Id.is_in(stale_revision_ids))
+            .exec(db)
+            .await?;
+    }
+    Ok(rm)
+}
+
 pub async fn find_certain_confluence_profiles_and_subscribe_sources(
     db: &DatabaseConnection,
     id: i32,
@@ -87,62 +270,364 @@ pub async fn find_certain_confluence_profiles_and_subscribe_sources(
     .map_err(AppError::from)
 }
 
-pub(crate) async fn passive_sync_one_subscribe_source_with_url(
-    sm: subscribe_source::Model,
+/// sync every subscribe source of a confluence but the passive ones, a failing source
+/// neither stops nor fails the others
+pub(crate) async fn sync_confluence_subscribe_sources(
+    sms: Vec<subscribe_source::Model>,
     ua: &str,
-    db: &DatabaseConnection,
-) -> Result<subscribe_source::Model, AppError> {
-    if sm.passive_sync.is_some_and(|ps| ps) {
-        return Ok(sm);
+    state: &AppState,
+) -> Vec<SubscribeSourceSyncResultDto> {
+    stream::iter(sms)
+        .map(|sm| async move {
+            let mut result = SubscribeSourceSyncResultDto {
+                subscribe_source_id: sm.id,
+                name: sm.name.clone(),
+                status: SubscribeSourceSyncStatus::Skipped,
+                error: None,
+            };
+            if sm.passive_sync.is_some_and(|ps| ps) {
+                return result;
+            }
+            match sync_one_subscribe_source_with_url(sm, ua, state).await {
+                Ok(Some(_)) => result.status = SubscribeSourceSyncStatus::Synced,
+                Ok(None) => result.status = SubscribeSourceSyncStatus::Unchanged,
+                Err(err) => {
+                    tracing::warn!(
+                        "sync subscribe source {} failed: {}",
+                        result.subscribe_source_id,
+                        err
+                    );
+                    result.status = SubscribeSourceSyncStatus::Failed;
+                    result.error = Some(err.to_string());
+                }
+            }
+            result
+        })
+        .buffered(SYNC_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// a successful response of a subscribe source url
+struct FetchedSubscription {
+    status: i32,
+    content: String,
+    sub_upload: Option<i64>,
+    sub_download: Option<i64>,
+    sub_total: Option<i64>,
+    sub_expire: Option<DateTime>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// none if the source answers not modified to the validators of its previous content
+async fn fetch_subscription(
+    sm: &subscribe_source::Model,
+    ua: &str,
+    fetcher: &Fetcher,
+    cipher: &SecretCipher,
+) -> Result<Option<FetchedSubscription>, AppError> {
+    let fetch_config = fetcher.config().with_overrides(sm.fetch_overrides.as_ref());
+    let proxy_server = cipher
+        .open_option(sm.proxy_server.as_deref())?
+        .filter(|p| !p.is_empty());
+    let client = fetcher.client(ClientKey {
+        user_agent: Some(ua.to_string()),
+        proxy_auth: cipher
+            .open_option(sm.proxy_auth.as_deref())?
+            .filter(|a| proxy_server.is_some() && !a.is_empty()),
+        proxy_server,
+        connect_timeout: fetch_config.connect_timeout,
+        read_timeout: fetch_config.read_timeout,
+    })?;
+    let mut request = client.get(cipher.open(&sm.url)?);
+    if let Some(request_headers) = &sm.request_headers {
+        let request_headers = open_request_headers(cipher, request_headers)?;
+        request = request.headers(parse_request_headers(&request_headers)?);
     }
-    sync_one_subscribe_source_with_url(sm, ua, db).await
+    // the content may have been cleared or never fetched, which no validator stands for
+    if !sm.content.is_empty() {
+        if let Some(etag) = &sm.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &sm.last_modified {
+            request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let res = fetch_with_retry(request, &fetch_config).await?;
+    if res.status == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+    check_subscription_response(&sm.name, &res, fetch_config.min_body_size)?;
+    let header_str = |name: HeaderName| {
+        res.headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header_str(header::ETAG);
+    let last_modified = header_str(header::LAST_MODIFIED);
+    let sub_userinfo = parse_subscription_userinfo_in_header(&res.headers).unwrap_or_default();
+    Ok(Some(FetchedSubscription {
+        status: res.status.as_u16() as i32,
+        sub_upload: sub_userinfo.get(SUB_UPLOAD).copied(),
+        sub_download: sub_userinfo.get(SUB_DOWNLOAD).copied(),
+        sub_total: sub_userinfo.get(SUB_TOTAL).copied(),
+        sub_expire: sub_userinfo
+            .get(SUB_EXPIRE)
+            .and_then(|v| chrono::DateTime::from_timestamp(*v, 0))
+            .map(|ts| ts.naive_utc()),
+        content: res.body,
+        etag,
+        last_modified,
+    }))
 }
 
+/// fetch the subscribe source and keep the payload, the sync status is recorded either way,
+/// none if the payload is unchanged and nothing was written
 pub async fn sync_one_subscribe_source_with_url(
     sm: subscribe_source::Model,
     ua: &str,
-    db: &DatabaseConnection,
-) -> Result<subscribe_source::Model, AppError> {
-    let mut client_builder = reqwest::ClientBuilder::new().user_agent(ua);
-
-    if let Some(proxy_server) = &sm.proxy_server {
-        if !proxy_server.is_empty() {
-            let mut proxy = reqwest::Proxy::all(proxy_server)?;
-            if let Some(proxy_auth) = &sm.proxy_auth {
-                if !proxy_auth.is_empty() {
-                    proxy = proxy.custom_http_auth(
-                        HeaderValue::from_str(proxy_auth)
-                            .map_err(|_| AppError::InvalidProxyAuthHeader)?,
-                    );
-                }
+    state: &AppState,
+) -> Result<Option<subscribe_source::Model>, AppError> {
+    let db = &state.conn;
+    let fetched = match fetch_subscription(&sm, ua, &state.fetcher, &state.cipher).await {
+        Ok(Some(fetched)) => fetched,
+        Ok(None) => {
+            // only clear a previous failure, an unchanged source is not written at all
+            if sm.consecutive_failures > 0 || sm.last_error.is_some() {
+                subscribe_source::Entity::update(subscribe_source::ActiveModel {
+                    id: Unchanged(sm.id),
+                    last_sync_at: Set(Some(Utc::now().naive_utc())),
+                    last_sync_status: Set(Some(StatusCode::NOT_MODIFIED.as_u16() as i32)),
+                    last_error: Set(None),
+                    consecutive_failures: Set(0),
+                    ..Default::default()
+                })
+                .exec(db)
+                .await?;
             }
-            client_builder = client_builder.proxy(proxy);
+            return Ok(None);
         }
-    }
+        Err(err) => {
+            let status = match &err {
+                AppError::Sync(e) => e.status().map(|s| s.as_u16() as i32),
+                _ => None,
+            };
+            // concurrent syncs of the same source must not lose a failure
+            subscribe_source::Entity::update_many()
+                .col_expr(
+                    subscribe_source::Column::LastSyncAt,
+                    Expr::value(Some(Utc::now().naive_utc())),
+                )
+                .col_expr(
+                    subscribe_source::Column::LastSyncStatus,
+                    Expr::value(status),
+                )
+                .col_expr(
+                    subscribe_source::Column::LastError,
+                    Expr::value(Some(err.to_string())),
+                )
+                .col_expr(
+                    subscribe_source::Column::ConsecutiveFailures,
+                    Expr::col(subscribe_source::Column::ConsecutiveFailures).add(1),
+                )
+                .filter(subscribe_source::Column::Id.eq(sm.id))
+                .exec(db)
+                .await?;
+            return Err(err);
+        }
+    };
+    let FetchedSubscription {
+        status,
+        content,
+        sub_upload,
+        sub_download,
+        sub_total,
+        sub_expire,
+        etag,
+        last_modified,
+    } = fetched;
 
-    let client = client_builder.build()?;
-    let res = client.get(&sm.url).send().await?;
+    let id = sm.id;
+    let pinned_snapshot_id = sm.pinned_snapshot_id;
     let mut sm = sm.into_active_model();
-    if let Some(sub_userinfo) = parse_subscription_userinfo_in_header(res.headers()) {
-        if let Some(v) = sub_userinfo.get(SUB_DOWNLOAD) {
-            sm.sub_download = Set(Some(*v));
-        };
-        if let Some(v) = sub_userinfo.get(SUB_UPLOAD) {
-            sm.sub_upload = Set(Some(*v));
-        };
-        if let Some(v) = sub_userinfo.get(SUB_TOTAL) {
-            sm.sub_total = Set(Some(*v));
-        };
-        if let Some(v) = sub_userinfo.get(SUB_EXPIRE) {
-            if let Some(ts) = chrono::DateTime::from_timestamp(*v, 0) {
-                sm.sub_expire = Set(Some(ts.naive_utc()));
-            }
-        };
+    if sub_upload.is_some() {
+        sm.sub_upload = Set(sub_upload);
+    }
+    if sub_download.is_some() {
+        sm.sub_download = Set(sub_download);
+    }
+    if sub_total.is_some() {
+        sm.sub_total = Set(sub_total);
+    }
+    if sub_expire.is_some() {
+        sm.sub_expire = Set(sub_expire);
+    }
+    sm.content = Set(content.clone());
+    sm.etag = Set(etag);
+    sm.last_modified = Set(last_modified);
+    sm.last_sync_at = Set(Some(Utc::now().naive_utc()));
+    sm.last_sync_status = Set(Some(status));
+    sm.last_error = Set(None);
+    sm.consecutive_failures = Set(0);
+
+    let txn = db.begin().await?;
+    let snapshot = subscribe_source_snapshot::ActiveModel {
+        subscribe_source_id: Set(id),
+        content_hash: Set(Some(content_hash(&content))),
+        content: Set(content),
+        status: Set(status),
+        sub_upload: Set(sub_upload),
+        sub_download: Set(sub_download),
+        sub_total: Set(sub_total),
+        sub_expire: Set(sub_expire),
+        ..Default::default()
     };
-    let content = res.text().await?;
-    sm.content = Set(content);
-    let sm = sm.update(db).await?;
-    Ok(sm)
+    record_subscribe_source_snapshot(&txn, id, pinned_snapshot_id, snapshot).await?;
+    let sm = sm.update(&txn).await?;
+    txn.commit().await?;
+    Ok(Some(sm))
+}
+
+/// store a fetched payload as a new snapshot, unless it is the payload of the latest snapshot,
+/// and drop the snapshots past the history limit except the pinned one
+async fn record_subscribe_source_snapshot<C: ConnectionTrait>(
+    db: &C,
+    subscribe_source_id: i32,
+    pinned_snapshot_id: Option<i32>,
+    snapshot: subscribe_source_snapshot::ActiveModel,
+) -> Result<(), AppError> {
+    let latest_content_hash = subscribe_source_snapshot::Entity::find()
+        .select_only()
+        .column(subscribe_source_snapshot::Column::ContentHash)
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(subscribe_source_id))
+        .order_by_desc(subscribe_source_snapshot::Column::Id)
+        .into_tuple::<Option<String>>()
+        .one(db)
+        .await?
+        .flatten();
+    if latest_content_hash.is_some()
+        && latest_content_hash.as_ref()
+            == snapshot.content_hash.try_as_ref().and_then(Option::as_ref)
+    {
+        return Ok(());
+    }
+    snapshot.insert(db).await?;
+    let stale_snapshot_ids = subscribe_source_snapshot::Entity::find()
+        .select_only()
+        .column(subscribe_source_snapshot::Column::Id)
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(subscribe_source_id))
+        .order_by_desc(subscribe_source_snapshot::Column::Id)
+        .offset(SNAPSHOT_HISTORY_LIMIT)
+        .into_tuple::<i32>()
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|snapshot_id| Some(*snapshot_id) != pinned_snapshot_id)
+        .collect::<Vec<_>>();
+    if !stale_snapshot_ids.is_empty() {
+        subscribe_source_snapshot::Entity::delete_many()
+            .filter(subscribe_source_snapshot::Column::Id.is_in(stale_snapshot_ids))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// the content a subscribe source is muxed with, its pinned snapshot if any, and the
+/// snapshot the content was fetched as
+pub async fn find_subscribe_source_mux_content(
+    db: &DatabaseConnection,
+    sm: &subscribe_source::Model,
+) -> Result<(String, Option<i32>), AppError> {
+    if let Some(snapshot_id) = sm.pinned_snapshot_id {
+        let snapshot = find_one_subscribe_source_snapshot_in_db(db, sm.id, snapshot_id).await?;
+        return Ok((snapshot.content, Some(snapshot.id)));
+    }
+    // the content may also be edited by hand, which no snapshot stands for
+    let snapshot_id = subscribe_source_snapshot::Entity::find()
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(sm.id))
+        .order_by_desc(subscribe_source_snapshot::Column::Id)
+        .one(db)
+        .await?
+        .filter(|s| s.content == sm.content)
+        .map(|s| s.id);
+    Ok((sm.content.clone(), snapshot_id))
+}
+
+/// the parsed config of a subscribe source to mux and the snapshot it was fetched as
+async fn load_subscribe_source_config(
+    db: &DatabaseConnection,
+    sm: &subscribe_source::Model,
+) -> Result<(ClashConfig, Option<i32>), AppError> {
+    let (content, snapshot_id) = find_subscribe_source_mux_content(db, sm).await?;
+    if content.is_empty() {
+        return Err(ConfigError::NotSync {
+            subscribe_source_name: sm.name.clone(),
+        }
+        .into());
+    }
+    let config = parse_subscription_content(&sm.name, &content)?;
+    Ok((config, snapshot_id))
+}
+
+/// the latest content of a subscribe source as a source which can be muxed on its own
+async fn load_mux_source<'a>(
+    db: &DatabaseConnection,
+    sm: &'a subscribe_source::Model,
+    options: &MuxSourceOptions,
+) -> Result<(MuxSource<'a>, Option<i32>), AppError> {
+    let (config, snapshot_id) = load_subscribe_source_config(db, sm).await?;
+    let source = MuxSource {
+        name: &sm.name,
+        config,
+        options: options.clone(),
+    };
+    check_mux_source(&source)?;
+    Ok((source, snapshot_id))
+}
+
+/// the latest snapshot of a subscribe source which parses and can be muxed on its own
+async fn find_last_valid_snapshot_source<'a>(
+    db: &DatabaseConnection,
+    sm: &'a subscribe_source::Model,
+    options: &MuxSourceOptions,
+) -> Result<Option<(MuxSource<'a>, i32)>, AppError> {
+    let ssms = subscribe_source_snapshot::Entity::find()
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(sm.id))
+        .order_by_desc(subscribe_source_snapshot::Column::Id)
+        .all(db)
+        .await?;
+    Ok(ssms.into_iter().find_map(|ssm| {
+        if ssm.content.is_empty() {
+            return None;
+        }
+        let source = MuxSource {
+            name: &sm.name,
+            config: parse_subscription_content(&sm.name, &ssm.content).ok()?,
+            options: options.clone(),
+        };
+        check_mux_source(&source).ok()?;
+        Some((source, ssm.id))
+    }))
+}
+
+pub async fn find_one_subscribe_source_snapshot_in_db(
+    db: &DatabaseConnection,
+    subscribe_source_id: i32,
+    snapshot_id: i32,
+) -> Result<subscribe_source_snapshot::Model, AppError> {
+    subscribe_source_snapshot::Entity::find_by_id(snapshot_id)
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(subscribe_source_id))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            AppError::DbNotFound(format!(
+                "cannot find snapshot id = {} of subscribe source id = {}",
+                snapshot_id, subscribe_source_id
+            ))
+        })
 }
 
 pub async fn find_one_confluence(
@@ -156,7 +641,7 @@ pub async fn find_one_confluence(
 
     let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
 
-    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms);
+    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms, &state.cipher);
     Ok(Json(confluence_dto))
 }
 
@@ -178,7 +663,7 @@ pub async fn find_many_confluences(
     )?;
 
     let confluences_dto = izip!(cms.into_iter(), pms.into_iter(), sms.into_iter())
-        .map(|(cm, pms, sms)| ConfluenceDto::from_orm(cm, sms, pms))
+        .map(|(cm, pms, sms)| ConfluenceDto::from_orm(cm, sms, pms, &state.cipher))
         .collect::<Vec<_>>();
 
     Ok(Json(confluences_dto))
@@ -204,7 +689,12 @@ pub async fn create_one_confluence(
 
     Ok((
         StatusCode::CREATED,
-        Json(ConfluenceDto::from_orm(confluence_model, vec![], vec![])),
+        Json(ConfluenceDto::from_orm(
+            confluence_model,
+            vec![],
+            vec![],
+            &state.cipher,
+        )),
     ))
 }
 
@@ -226,12 +716,24 @@ pub async fn update_one_confluence(
     if let Some(name) = confluence_update_dto.name {
         cm.name = Set(name);
     }
+    if let Some(source_auto_group) = confluence_update_dto.source_auto_group {
+        cm.source_auto_group = Set(source_auto_group);
+    }
+    if let Some(degraded_mux) = confluence_update_dto.degraded_mux {
+        cm.degraded_mux = Set(degraded_mux);
+    }
+    if let Some(template_id) = confluence_update_dto.template_id {
+        if let Some(template_id) = template_id {
+            find_one_visible_template_in_db(db, template_id, &current_user).await?;
+        }
+        cm.template_id = Set(template_id);
+    }
     cm = cm.save(db).await?;
     let cm = cm.try_into_model()?;
 
     let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
 
-    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms);
+    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms, &state.cipher);
 
     Ok(Json(confluence_dto))
 }
@@ -252,7 +754,7 @@ pub async fn update_one_confluence_cron(
         .cron_expr_tz
         .parse::<Tz>()
         .map_err(|_| AppError::BadRequest {
-            message: format!("bad timezone {}", &confluence_update_cron_dto.cron_expr_tz),
+            message: format!("bad timezone {}", confluence_update_cron_dto.cron_expr_tz),
         })?;
 
     if let Some(next_time) = schedule.upcoming(tz).take(1).next() {
@@ -284,15 +786,15 @@ pub async fn sync_one_confluence(
 
     let ua = cm.user_agent_or_default();
 
-    let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
+    let (_, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
 
-    let sms = try_join_all(
-        sms.into_iter()
-            .map(|sm| passive_sync_one_subscribe_source_with_url(sm, ua, db)),
-    )
-    .await?;
+    let sync_results = sync_confluence_subscribe_sources(sms, ua, &state).await;
 
-    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms);
+    // reload to pick up the content and sync status of every source
+    let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
+
+    let mut confluence_dto = ConfluenceDto::from_orm(cm, sms, pms, &state.cipher);
+    confluence_dto.sync_results = Some(sync_results);
 
     Ok(Json(confluence_dto))
 }
@@ -310,22 +812,52 @@ pub async fn mux_one_confluence_impl(
     ),
     AppError,
 > {
-    let template = serde_yaml::from_str::<ClashConfig>(&cm.template).map_err(ConfigError::from)?;
+    let template = find_confluence_template_content(db, &cm).await?;
+    let template = serde_yaml::from_str::<ClashConfig>(&template).map_err(ConfigError::from)?;
     let mut sources = vec![];
     let mut sub_upload: Option<i64> = None;
     let mut sub_download: Option<i64> = None;
     let mut sub_expire: Option<DateTime> = None;
     let mut sub_total: Option<i64> = None;
+    let mut revision_sources = vec![];
+    let mut failures = vec![];
+    let mut skipped_sources = vec![];
     for sm in &sms {
-        let source = &sm.content as &str;
         let name = &sm.name as &str;
-        if source.is_empty() {
-            return Err(ConfigError::NotSync {
-                subscribe_source_name: name.to_string(),
+        // options are validated when stored, only the fetched content may break
+        let mut options = MuxSourceOptions::try_from(sm)?;
+        options.auto_group = cm.source_auto_group.clone();
+        let (source, snapshot_id) = match load_mux_source(db, sm, &options).await {
+            Ok(loaded) => loaded,
+            Err(AppError::Config(err)) if cm.degraded_mux => {
+                let fallback = find_last_valid_snapshot_source(db, sm, &options).await?;
+                tracing::warn!(
+                    "mux confluence {} without the latest content of subscribe source {}: {}",
+                    cm.id,
+                    sm.id,
+                    err
+                );
+                failures.push(MuxFailure {
+                    subscribe_source_id: sm.id,
+                    name: name.to_string(),
+                    reason: err.to_string(),
+                    fallback_snapshot_id: fallback.as_ref().map(|(_, id)| *id),
+                });
+                match fallback {
+                    Some((source, snapshot_id)) => (source, Some(snapshot_id)),
+                    None => {
+                        skipped_sources.push(name);
+                        continue;
+                    }
+                }
             }
-            .into());
-        }
-        let config: ClashConfig = serde_yaml::from_str(source).map_err(ConfigError::from)?;
+            Err(err) => return Err(err),
+        };
+        revision_sources.push(RevisionSource {
+            subscribe_source_id: sm.id,
+            name: name.to_string(),
+            snapshot_id,
+        });
         sub_upload = match (sub_upload, sm.sub_upload) {
             (None, None) => None,
             (acc, curr) => Some(acc.unwrap_or_default() + curr.unwrap_or_default()),
@@ -349,17 +881,33 @@ pub async fn mux_one_confluence_impl(
                 }
             }
         };
-        sources.push((name, config));
+        sources.push(source);
     }
-    let mux_config = mux_configs(cm.name.as_str(), &template, &sources)?;
+    if sources.is_empty() && !failures.is_empty() {
+        // keep serving the last good mux rather than one without any source
+        return Err(ConfigError::SourcesUnavailable {
+            reasons: failures
+                .iter()
+                .map(|f| format!("{}: {}", f.name, f.reason))
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+        .into());
+    }
+    let mux_config = mux_configs(cm.name.as_str(), &template, &sources, &skipped_sources)?;
     let mux_content = serde_yaml::to_string(&mux_config).map_err(ConfigError::from)?;
+    let txn = db.begin().await?;
+    record_confluence_revision(&txn, cm.id, &mux_content, RevisionSources(revision_sources))
+        .await?;
     let mut cm = cm.into_active_model();
     cm.mux_content = Set(mux_content);
+    cm.mux_failures = Set((!failures.is_empty()).then_some(MuxFailures(failures)));
     cm.sub_download = Set(sub_upload);
     cm.sub_expire = Set(sub_expire);
     cm.sub_total = Set(sub_total);
     cm.sub_upload = Set(sub_download);
-    let cm = cm.update(db).await?;
+    let cm = cm.update(&txn).await?;
+    txn.commit().await?;
     Ok((cm, sms, pms))
 }
 
@@ -376,11 +924,96 @@ pub async fn mux_one_confluence(
 
     let (cm, sms, pms) = mux_one_confluence_impl(db, cm, sms, pms).await?;
 
-    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms);
+    let confluence_dto = ConfluenceDto::from_orm(cm, sms, pms, &state.cipher);
 
     Ok(Json(confluence_dto))
 }
 
+pub async fn find_many_confluence_revisions(
+    Path(id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<Vec<ConfluenceRevisionDto>>, AppError> {
+    let db = &state.conn;
+    find_one_confluence_in_db(db, id, &current_user).await?;
+    let rms = confluence_revision::Entity::find()
+        .filter(confluence_revision::Column::ConfluenceId.eq(id))
+        .order_by_desc(confluence_revision::Column::Id)
+        .all(db)
+        .await?;
+    Ok(Json(
+        rms.into_iter()
+            .map(|r| ConfluenceRevisionDto::from_orm(r, false))
+            .collect(),
+    ))
+}
+
+pub async fn find_one_confluence_revision(
+    Path((id, revision_id)): Path<(i32, i32)>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<ConfluenceRevisionDto>, AppError> {
+    let db = &state.conn;
+    find_one_confluence_in_db(db, id, &current_user).await?;
+    let rm = find_one_confluence_revision_in_db(db, id, revision_id).await?;
+    Ok(Json(ConfluenceRevisionDto::from_orm(rm, true)))
+}
+
+/// what changed in a revision against another one, or against the previous revision
+pub async fn find_one_confluence_revision_diff(
+    Path((id, revision_id)): Path<(i32, i32)>,
+    Query(query): Query<RevisionDiffQueryDto>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<MuxDiff>, AppError> {
+    let db = &state.conn;
+    find_one_confluence_in_db(db, id, &current_user).await?;
+    let rm = find_one_confluence_revision_in_db(db, id, revision_id).await?;
+    let against = match query.against {
+        Some(against) => Some(find_one_confluence_revision_in_db(db, id, against).await?),
+        None => {
+            confluence_revision::Entity::find()
+                .filter(confluence_revision::Column::ConfluenceId.eq(id))
+                .filter(confluence_revision::Column::Id.lt(revision_id))
+                .order_by_desc(confluence_revision::Column::Id)
+                .one(db)
+                .await?
+        }
+    };
+    let new = serde_yaml::from_str::<ClashConfig>(&rm.content).map_err(ConfigError::from)?;
+    // the first revision adds everything
+    let old = against
+        .map(|r| serde_yaml::from_str::<ClashConfig>(&r.content))
+        .transpose()
+        .map_err(ConfigError::from)?
+        .unwrap_or_default();
+    Ok(Json(diff_configs(&old, &new)))
+}
+
+/// serve the content of an earlier revision again, recorded as the latest revision,
+/// it is served until the next mux records a newer revision, which happens as soon as
+/// the cron syncs a changed source or the confluence is updated, pin profiles to the
+/// revision with `revision_id` to keep serving it
+pub async fn rollback_one_confluence_revision(
+    Path((id, revision_id)): Path<(i32, i32)>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<ConfluenceDto>, AppError> {
+    let db = &state.conn;
+    let cm = find_one_confluence_in_db(db, id, &current_user).await?;
+    let rm = find_one_confluence_revision_in_db(db, id, revision_id).await?;
+    let txn = db.begin().await?;
+    record_confluence_revision(&txn, id, &rm.content, rm.sources).await?;
+    let mut cm = cm.into_active_model();
+    cm.mux_content = Set(rm.content);
+    let cm = cm.update(&txn).await?;
+    txn.commit().await?;
+
+    let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
+
+    Ok(Json(ConfluenceDto::from_orm(cm, sms, pms, &state.cipher)))
+}
+
 pub async fn delete_one_confluence(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -395,6 +1028,7 @@ pub async fn delete_one_confluence(
 
 pub async fn find_one_profile_as_subscription_by_token(
     Path(token): Path<String>,
+    Query(query): Query<ProfileTokenQueryDto>,
     State(state): State<Arc<AppState>>,
 ) -> Result<(HeaderMap, String), AppError> {
     let db = &state.conn;
@@ -404,18 +1038,20 @@ pub async fn find_one_profile_as_subscription_by_token(
         .limit(1)
         .all(db)
         .await?;
-    if let Some((_, mut cms)) = pms.pop() {
+    if let Some((pm, mut cms)) = pms.pop() {
         let cm = cms.pop().ok_or_else(|| {
             AppError::DbNotFound(format!("cannot find profile token = {}", token))
         })?;
+        let target = query.target.unwrap_or(pm.target);
         let mut headers = HeaderMap::new();
         headers.insert(
             header::CONTENT_TYPE,
-            HeaderValue::from_static("application/octet-stream; charset=utf-8"),
+            HeaderValue::from_static(target.content_type()),
         );
         headers.insert(
-            header::CONTENT_TYPE,
-            HeaderValue::from_static("attachment; filename=Confluence.yaml"),
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_str(&format!("attachment; filename={}", target.file_name()))
+                .map_err(|err| AppError::Other(err.into()))?,
         );
         let sub_expr_to_part = |a: DateTime| {
             let ts = a.and_utc().timestamp();
@@ -442,8 +1078,24 @@ pub async fn find_one_profile_as_subscription_by_token(
                 );
             }
         };
-        let mux_content = cm.mux_content;
-        Ok((headers, mux_content))
+        let mux_content = match pm.revision_id {
+            Some(revision_id) => {
+                find_one_confluence_revision_in_db(db, cm.id, revision_id)
+                    .await?
+                    .content
+            }
+            None => cm.mux_content,
+        };
+        let rendered = render_profile(&mux_content, target)?;
+        for diagnostic in &rendered.diagnostics {
+            tracing::warn!(
+                "render profile {} as {}: {}",
+                pm.id,
+                target.name(),
+                diagnostic
+            );
+        }
+        Ok((headers, rendered.content))
     } else {
         Err(AppError::DbNotFound(format!(
             "cannot find profile token = {}",
@@ -462,6 +1114,7 @@ pub async fn create_one_profile(
     let mut pms = profile::ActiveModel {
         resource_token: Set(Uuid::new_v4().to_string()),
         confluence_id: Set(profile_creation_dto.confluence_id),
+        target: Set(profile_creation_dto.target.unwrap_or_default()),
         ..Default::default()
     };
     pms = pms.save(db).await?;
@@ -469,6 +1122,42 @@ pub async fn create_one_profile(
     Ok(Json(pms.into()))
 }
 
+pub async fn update_one_profile(
+    Path(id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(profile_update_dto): Json<ProfileUpdateDto>,
+) -> Result<Json<ProfileDto>, AppError> {
+    let db = &state.conn;
+    let mut pm = profile::Entity::find_by_id(id)
+        .find_with_related(confluence::Entity)
+        .filter(confluence::Column::Creator.eq(&current_user.user_id))
+        .limit(1)
+        .all(db)
+        .await?;
+    if let Some((pm, _)) = pm.pop() {
+        let confluence_id = pm.confluence_id;
+        let mut pam = pm.into_active_model();
+        if let Some(target) = profile_update_dto.target {
+            pam.target = Set(target);
+        }
+        if let Some(revision_id) = profile_update_dto.revision_id {
+            if let Some(revision_id) = revision_id {
+                find_one_confluence_revision_in_db(db, confluence_id, revision_id).await?;
+            }
+            pam.revision_id = Set(revision_id);
+        }
+        let pam = pam.save(db).await?;
+        let pm = pam.try_into_model()?;
+        Ok(Json(pm.into()))
+    } else {
+        Err(AppError::DbNotFound(format!(
+            "cannot find profile id = {}",
+            id
+        )))
+    }
+}
+
 pub async fn delete_one_profile(
     Path(id): Path<i32>,
     State(state): State<Arc<AppState>>,
@@ -493,6 +1182,408 @@ pub async fn delete_one_profile(
     }
 }
 
+/// reject template content which is not a clash config before it is stored
+fn validate_template_content(content: &str) -> Result<(), AppError> {
+    serde_yaml::from_str::<ClashConfig>(content)
+        .map(|_| ())
+        .map_err(|e| AppError::BadRequest {
+            message: format!("invalid template, caused by {}", e),
+        })
+}
+
+/// re-mux every confluence following the template, a failing confluence does not stop the others
+async fn mux_template_followers(db: &DatabaseConnection, template_id: i32) -> Result<(), AppError> {
+    let cms = confluence::Entity::find()
+        .filter(confluence::Column::TemplateId.eq(template_id))
+        .all(db)
+        .await?;
+    for cm in cms {
+        let id = cm.id;
+        let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, id).await?;
+        if let Err(err) = mux_one_confluence_impl(db, cm, sms, pms).await {
+            tracing::warn!(
+                "mux confluence {} with template {} failed: {}",
+                id,
+                template_id,
+                err
+            );
+        }
+    }
+    Ok(())
+}
+
+pub async fn find_many_templates(
+    Extension(current_user): Extension<CurrentUser>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TemplateDto>>, AppError> {
+    let db = &state.conn;
+    let tms = template::Entity::find()
+        .filter(
+            Condition::any()
+                .add(template::Column::Creator.eq(&current_user.user_id))
+                .add(template::Column::Shared.eq(true)),
+        )
+        .order_by_asc(template::Column::Id)
+        .all(db)
+        .await?;
+    Ok(Json(tms.into_iter().map(|t| t.into()).collect()))
+}
+
+pub async fn find_one_template(
+    Path(id): Path<i32>,
+    Extension(current_user): Extension<CurrentUser>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<TemplateDto>, AppError> {
+    let db = &state.conn;
+    let tm = find_one_visible_template_in_db(db, id, &current_user).await?;
+    Ok(Json(tm.into()))
+}
+
+pub async fn find_many_template_versions(
+    Path(id): Path<i32>,
+    Extension(current_user): Extension<CurrentUser>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<TemplateVersionDto>>, AppError> {
+    let db = &state.conn;
+    find_one_visible_template_in_db(db, id, &current_user).await?;
+    let tvms = template_version::Entity::find()
+        .filter(template_version::Column::TemplateId.eq(id))
+        .order_by_desc(template_version::Column::Version)
+        .all(db)
+        .await?;
+    Ok(Json(tvms.into_iter().map(|t| t.into()).collect()))
+}
+
+pub async fn create_one_template(
+    Extension(current_user): Extension<CurrentUser>,
+    State(state): State<Arc<AppState>>,
+    Json(template_creation_dto): Json<TemplateCreationDto>,
+) -> Result<(StatusCode, Json<TemplateDto>), AppError> {
+    let db = &state.conn;
+    validate_template_content(&template_creation_dto.content)?;
+    let txn = db.begin().await?;
+    let tm = template::ActiveModel {
+        name: Set(template_creation_dto.name),
+        creator: Set(current_user.user_id),
+        content: Set(template_creation_dto.content),
+        shared: Set(template_creation_dto.shared.unwrap_or_default()),
+        version: Set(1),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    template_version::ActiveModel {
+        template_id: Set(tm.id),
+        version: Set(tm.version),
+        content: Set(tm.content.clone()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+    Ok((StatusCode::CREATED, Json(tm.into())))
+}
+
+pub async fn update_one_template(
+    Path(id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(template_update_dto): Json<TemplateUpdateDto>,
+) -> Result<Json<TemplateDto>, AppError> {
+    let db = &state.conn;
+    let tm = find_one_owned_template_in_db(db, id, &current_user).await?;
+    let content = template_update_dto
+        .content
+        .filter(|content| content != &tm.content);
+    if let Some(content) = &content {
+        validate_template_content(content)?;
+    }
+    let version = tm.version + 1;
+    let mut tam = tm.into_active_model();
+    if let Some(name) = template_update_dto.name {
+        tam.name = Set(name);
+    }
+    if let Some(shared) = template_update_dto.shared {
+        tam.shared = Set(shared);
+    }
+    let txn = db.begin().await?;
+    if let Some(content) = &content {
+        tam.content = Set(content.clone());
+        tam.version = Set(version);
+        template_version::ActiveModel {
+            template_id: Set(id),
+            version: Set(version),
+            content: Set(content.clone()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+    }
+    let tam = tam.save(&txn).await?;
+    txn.commit().await?;
+    let tm = tam.try_into_model()?;
+    if content.is_some() {
+        // the update is committed already, followers are re-muxed in the background
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(err) = mux_template_followers(&state.conn, id).await {
+                tracing::warn!("mux followers of template {} failed: {}", id, err);
+            }
+        });
+    }
+    Ok(Json(tm.into()))
+}
+
+pub async fn delete_one_template(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    let db = &state.conn;
+    let tm = find_one_owned_template_in_db(db, id, &current_user).await?;
+    let txn = db.begin().await?;
+    // followers keep muxing with the last content as their inline template
+    confluence::Entity::update_many()
+        .col_expr(
+            confluence::Column::Template,
+            Expr::value(tm.content.clone()),
+        )
+        .col_expr(
+            confluence::Column::TemplateId,
+            Expr::value(Option::<i32>::None),
+        )
+        .filter(confluence::Column::TemplateId.eq(id))
+        .exec(&txn)
+        .await?;
+    tm.into_active_model().delete(&txn).await?;
+    txn.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+/// reject proxy filter patterns which do not compile before they are stored
+fn validate_proxy_filter(
+    name: &str,
+    include: Option<&ProxyMatcher>,
+    exclude: Option<&ProxyMatcher>,
+    rename: Option<&ProxyRenameRules>,
+) -> Result<(), AppError> {
+    let rename = rename.map(|r| r.0.as_slice()).unwrap_or_default();
+    ProxyFilter::new(name, include, exclude, rename)
+        .map(|_| ())
+        .map_err(|e| AppError::BadRequest {
+            message: e.to_string(),
+        })
+}
+
+/// reject fetch overrides past the server maximums before they are stored
+fn validate_fetch_overrides(
+    config: &FetchConfig,
+    overrides: Option<&FetchOverrides>,
+) -> Result<(), AppError> {
+    overrides
+        .map_or(Ok(()), |o| config.check_overrides(o))
+        .map_err(|message| AppError::BadRequest { message })
+}
+
+/// reject proxy settings which can not be connected through before they are stored
+fn validate_upstream_proxy(
+    proxy_server: Option<&str>,
+    proxy_auth: Option<&str>,
+) -> Result<(), AppError> {
+    match proxy_server.filter(|p| !p.is_empty()) {
+        Some(proxy_server) => ProxySettings::from_columns(proxy_server, proxy_auth)
+            .map(|_| ())
+            .map_err(|e| AppError::BadRequest {
+                message: e.to_string(),
+            }),
+        None if proxy_auth.is_some_and(|a| !a.is_empty()) => Err(AppError::BadRequest {
+            message: String::from("proxy auth without proxy server"),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub async fn test_upstream_proxy(
+    State(state): State<Arc<AppState>>,
+    Json(proxy_test_dto): Json<ProxyTestDto>,
+) -> Result<Json<ProxyTestResultDto>, AppError> {
+    let proxy = proxy_test_dto.proxy;
+    proxy.validate().map_err(|e| AppError::BadRequest {
+        message: e.to_string(),
+    })?;
+    let client = if state.fetcher.config().allow_private_proxies {
+        state.fetcher.build_client(&ClientKey {
+            proxy_server: Some(proxy.server_url()),
+            proxy_auth: proxy.auth_header(),
+            ..ClientKey::new(state.fetcher.config())
+        })?
+    } else {
+        let addr = proxy
+            .resolve_public_addr()
+            .await
+            .ok_or_else(|| AppError::BadRequest {
+                message: String::from("proxy host is not public"),
+            })?;
+        state.fetcher.build_pinned_proxy_client(&proxy, addr)?
+    };
+    let started_at = std::time::Instant::now();
+    let res = client.get(PROXY_TEST_URL).send().await;
+    let latency_ms = started_at.elapsed().as_millis() as i64;
+    if let Err(e) = &res {
+        tracing::debug!("proxy test of {} failed: {}", proxy.server_url(), e);
+    }
+    Ok(Json(ProxyTestResultDto {
+        reachable: res.is_ok(),
+        latency_ms,
+    }))
+}
+
+fn open_request_headers(
+    cipher: &SecretCipher,
+    headers: &RequestHeaders,
+) -> Result<RequestHeaders, AppError> {
+    Ok(RequestHeaders(
+        headers
+            .0
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), cipher.open(value)?)))
+            .collect::<Result<_, AppError>>()?,
+    ))
+}
+
+/// validate request headers and seal their values, a masked value keeps the stored value
+/// of the same header
+fn seal_request_headers(
+    cipher: &SecretCipher,
+    headers: RequestHeaders,
+    current: Option<&RequestHeaders>,
+) -> Result<RequestHeaders, AppError> {
+    let mut plain = RequestHeaders::default();
+    let mut sealed = RequestHeaders::default();
+    for (name, value) in headers.0 {
+        let current_value = current.and_then(|c| {
+            c.0.iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(&name))
+                .map(|(_, v)| v)
+        });
+        match current_value {
+            Some(current_value) if value == MASK => {
+                plain.0.insert(name.clone(), cipher.open(current_value)?);
+                sealed.0.insert(name, current_value.clone());
+            }
+            _ => {
+                sealed.0.insert(name.clone(), cipher.seal(&value)?);
+                plain.0.insert(name, value);
+            }
+        }
+    }
+    parse_request_headers(&plain).map_err(|e| AppError::BadRequest {
+        message: e.to_string(),
+    })?;
+    Ok(sealed)
+}
+
+pub async fn find_one_subscribe_source(
+    Path(id): Path<i32>,
+    Query(query): Query<SubscribeSourceQueryDto>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<SubscribeSourceDto>, AppError> {
+    let sm = find_one_subscribe_source_in_db(&state.conn, id, &current_user).await?;
+    Ok(Json(SubscribeSourceDto::from_orm(
+        sm,
+        &state.cipher,
+        query.reveal.unwrap_or_default(),
+    )))
+}
+
+/// the sealed columns of a subscribe source
+#[derive(FromQueryResult)]
+struct SubscribeSourceSecrets {
+    id: i32,
+    url: String,
+    proxy_server: Option<String>,
+    proxy_auth: Option<String>,
+    request_headers: Option<RequestHeaders>,
+}
+
+/// seal secrets stored in plain text and rewrap those sealed under a retired key,
+/// returns the number of subscribe sources written, run on every startup so existing
+/// rows are sealed once keys are configured, there is no migration doing it
+pub async fn reseal_subscribe_source_secrets(
+    db: &DatabaseConnection,
+    cipher: &SecretCipher,
+) -> Result<usize, AppError> {
+    if !cipher.is_enabled() {
+        return Ok(0);
+    }
+    let needs_reseal = |v: &Option<String>| v.as_deref().is_some_and(|v| cipher.needs_reseal(v));
+    let reseal = |v: Option<String>| v.map(|v| cipher.reseal(&v)).transpose();
+    let mut resealed = 0;
+    let mut last_id = None;
+    loop {
+        let mut query = subscribe_source::Entity::find()
+            .select_only()
+            .columns([
+                subscribe_source::Column::Id,
+                subscribe_source::Column::Url,
+                subscribe_source::Column::ProxyServer,
+                subscribe_source::Column::ProxyAuth,
+                subscribe_source::Column::RequestHeaders,
+            ])
+            .order_by_asc(subscribe_source::Column::Id)
+            .limit(RESEAL_BATCH_SIZE);
+        if let Some(last_id) = last_id {
+            query = query.filter(subscribe_source::Column::Id.gt(last_id));
+        }
+        let rows = query.into_model::<SubscribeSourceSecrets>().all(db).await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        last_id = Some(last.id);
+        let is_last_batch = (rows.len() as u64) < RESEAL_BATCH_SIZE;
+        let txn = db.begin().await?;
+        for row in rows {
+            let headers_need_reseal = row
+                .request_headers
+                .as_ref()
+                .is_some_and(|h| h.0.values().any(|v| cipher.needs_reseal(v)));
+            if !cipher.needs_reseal(&row.url)
+                && !needs_reseal(&row.proxy_server)
+                && !needs_reseal(&row.proxy_auth)
+                && !headers_need_reseal
+            {
+                continue;
+            }
+            let request_headers = row
+                .request_headers
+                .map(|h| {
+                    h.0.into_iter()
+                        .map(|(name, value)| Ok((name, cipher.reseal(&value)?)))
+                        .collect::<Result<_, AppError>>()
+                        .map(RequestHeaders)
+                })
+                .transpose()?;
+            subscribe_source::Entity::update(subscribe_source::ActiveModel {
+                id: Unchanged(row.id),
+                url: Set(cipher.reseal(&row.url)?),
+                proxy_server: Set(reseal(row.proxy_server)?),
+                proxy_auth: Set(reseal(row.proxy_auth)?),
+                request_headers: Set(request_headers),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?;
+            resealed += 1;
+        }
+        txn.commit().await?;
+        if is_last_batch {
+            break;
+        }
+    }
+    Ok(resealed)
+}
+
 pub async fn create_one_subscribe_source(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -500,19 +1591,49 @@ pub async fn create_one_subscribe_source(
 ) -> Result<Json<SubscribeSourceDto>, AppError> {
     let db = &state.conn;
     find_one_confluence_in_db(db, subscribe_creation_dto.confluence_id, &current_user).await?;
+    validate_proxy_filter(
+        &subscribe_creation_dto.name,
+        subscribe_creation_dto.proxy_include.as_ref(),
+        subscribe_creation_dto.proxy_exclude.as_ref(),
+        subscribe_creation_dto.proxy_rename.as_ref(),
+    )?;
+    validate_fetch_overrides(
+        state.fetcher.config(),
+        subscribe_creation_dto.fetch_overrides.as_ref(),
+    )?;
+    let (proxy_server, proxy_auth) = match &subscribe_creation_dto.proxy {
+        Some(proxy) => (Some(proxy.server_url()), proxy.auth_header()),
+        None => (
+            subscribe_creation_dto.proxy_server,
+            subscribe_creation_dto.proxy_auth,
+        ),
+    };
+    validate_upstream_proxy(proxy_server.as_deref(), proxy_auth.as_deref())?;
+    let cipher = &state.cipher;
+    let request_headers = subscribe_creation_dto
+        .request_headers
+        .map(|h| seal_request_headers(cipher, h, None))
+        .transpose()?;
     let mut pms = subscribe_source::ActiveModel {
         confluence_id: Set(subscribe_creation_dto.confluence_id),
-        url: Set(subscribe_creation_dto.url),
+        url: Set(cipher.seal(&subscribe_creation_dto.url)?),
         name: Set(subscribe_creation_dto.name),
         content: Set(String::new()),
         passive_sync: Set(subscribe_creation_dto.passive_sync),
-        proxy_auth: Set(subscribe_creation_dto.proxy_auth),
-        proxy_server: Set(subscribe_creation_dto.proxy_server),
+        proxy_auth: Set(cipher.seal_option(proxy_auth)?),
+        proxy_server: Set(cipher.seal_option(proxy_server)?),
+        import_proxy_groups: Set(subscribe_creation_dto.import_proxy_groups),
+        proxy_include: Set(subscribe_creation_dto.proxy_include),
+        proxy_exclude: Set(subscribe_creation_dto.proxy_exclude),
+        proxy_rename: Set(subscribe_creation_dto.proxy_rename),
+        tags: Set(subscribe_creation_dto.tags.unwrap_or_default()),
+        fetch_overrides: Set(subscribe_creation_dto.fetch_overrides),
+        request_headers: Set(request_headers),
         ..Default::default()
     };
     pms = pms.save(db).await?;
     let pms = pms.try_into_model()?;
-    Ok(Json(pms.into()))
+    Ok(Json(SubscribeSourceDto::from_orm(pms, cipher, false)))
 }
 
 pub async fn update_one_subscribe_source(
@@ -529,12 +1650,72 @@ pub async fn update_one_subscribe_source(
         .all(db)
         .await?;
     if let Some(pm) = pm.pop() {
+        validate_proxy_filter(
+            &pm.0.name,
+            subscribe_update_dto
+                .proxy_include
+                .as_ref()
+                .and_then(Option::as_ref),
+            subscribe_update_dto
+                .proxy_exclude
+                .as_ref()
+                .and_then(Option::as_ref),
+            subscribe_update_dto
+                .proxy_rename
+                .as_ref()
+                .and_then(Option::as_ref),
+        )?;
+        let cipher = &state.cipher;
+        // masked values sent back as they were received leave the secrets unchanged
+        let masked = SubscribeSourceDto::from_orm(pm.0.clone(), cipher, false);
+        let current_proxy_server = cipher.open_option(pm.0.proxy_server.as_deref())?;
+        let current_proxy_auth = cipher.open_option(pm.0.proxy_auth.as_deref())?;
+        let (proxy_server, proxy_auth) = match subscribe_update_dto.proxy {
+            Some(Some(mut proxy)) => {
+                if proxy.password.as_deref() == Some(MASK) {
+                    proxy.password = current_proxy_server
+                        .as_deref()
+                        .filter(|p| !p.is_empty())
+                        .and_then(|p| {
+                            ProxySettings::from_columns(p, current_proxy_auth.as_deref()).ok()
+                        })
+                        .and_then(|p| p.password);
+                }
+                (Some(proxy.server_url()), proxy.auth_header())
+            }
+            Some(None) => (None, None),
+            None => (
+                subscribe_update_dto
+                    .proxy_server
+                    .filter(|p| Some(p) != masked.proxy_server.as_ref())
+                    .or(current_proxy_server),
+                subscribe_update_dto
+                    .proxy_auth
+                    .filter(|a| Some(a) != masked.proxy_auth.as_ref())
+                    .or(current_proxy_auth),
+            ),
+        };
+        validate_upstream_proxy(proxy_server.as_deref(), proxy_auth.as_deref())?;
+        let request_headers = subscribe_update_dto
+            .request_headers
+            .map(|h| seal_request_headers(cipher, h, pm.0.request_headers.as_ref()))
+            .transpose()?;
         let mut pam = pm.0.into_active_model();
+        if let Some(request_headers) = request_headers {
+            pam.request_headers = Set(Some(request_headers));
+        }
+        pam.proxy_server = Set(cipher.seal_option(proxy_server)?);
+        pam.proxy_auth = Set(cipher.seal_option(proxy_auth)?);
         if let Some(name) = subscribe_update_dto.name {
             pam.name = Set(name);
         }
-        if let Some(url) = subscribe_update_dto.url {
-            pam.url = Set(url);
+        if let Some(url) = subscribe_update_dto.url
+            && url != masked.url
+        {
+            // validators of another url say nothing about this one
+            pam.etag = Set(None);
+            pam.last_modified = Set(None);
+            pam.url = Set(cipher.seal(&url)?);
         };
         if let Some(content) = subscribe_update_dto.content {
             pam.content = Set(content);
@@ -542,15 +1723,34 @@ pub async fn update_one_subscribe_source(
         if let Some(passive_sync) = subscribe_update_dto.passive_sync {
             pam.passive_sync = Set(Some(passive_sync));
         };
-        if let Some(proxy_auth) = subscribe_update_dto.proxy_auth {
-            pam.proxy_auth = Set(Some(proxy_auth));
+        if let Some(import_proxy_groups) = subscribe_update_dto.import_proxy_groups {
+            pam.import_proxy_groups = Set(Some(import_proxy_groups));
+        };
+        if let Some(proxy_include) = subscribe_update_dto.proxy_include {
+            pam.proxy_include = Set(proxy_include);
+        };
+        if let Some(proxy_exclude) = subscribe_update_dto.proxy_exclude {
+            pam.proxy_exclude = Set(proxy_exclude);
+        };
+        if let Some(proxy_rename) = subscribe_update_dto.proxy_rename {
+            pam.proxy_rename = Set(proxy_rename);
+        };
+        if let Some(tags) = subscribe_update_dto.tags {
+            pam.tags = Set(tags);
         };
-        if let Some(proxy_server) = subscribe_update_dto.proxy_server {
-            pam.proxy_server = Set(Some(proxy_server));
+        if let Some(pinned_snapshot_id) = subscribe_update_dto.pinned_snapshot_id {
+            if let Some(snapshot_id) = pinned_snapshot_id {
+                find_one_subscribe_source_snapshot_in_db(db, id, snapshot_id).await?;
+            }
+            pam.pinned_snapshot_id = Set(pinned_snapshot_id);
+        };
+        if let Some(fetch_overrides) = subscribe_update_dto.fetch_overrides {
+            validate_fetch_overrides(state.fetcher.config(), fetch_overrides.as_ref())?;
+            pam.fetch_overrides = Set(fetch_overrides);
         };
         let pam = pam.save(db).await?;
         let pm = pam.try_into_model()?;
-        Ok(Json(pm.into()))
+        Ok(Json(SubscribeSourceDto::from_orm(pm, cipher, false)))
     } else {
         Err(AppError::DbNotFound(format!(
             "cannot find subscribe source id = {}",
@@ -559,6 +1759,36 @@ pub async fn update_one_subscribe_source(
     }
 }
 
+pub async fn find_many_subscribe_source_snapshots(
+    Path(id): Path<i32>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<Vec<SubscribeSourceSnapshotDto>>, AppError> {
+    let db = &state.conn;
+    find_one_subscribe_source_in_db(db, id, &current_user).await?;
+    let ssms = subscribe_source_snapshot::Entity::find()
+        .filter(subscribe_source_snapshot::Column::SubscribeSourceId.eq(id))
+        .order_by_desc(subscribe_source_snapshot::Column::Id)
+        .all(db)
+        .await?;
+    Ok(Json(
+        ssms.into_iter()
+            .map(|s| SubscribeSourceSnapshotDto::from_orm(s, false))
+            .collect(),
+    ))
+}
+
+pub async fn find_one_subscribe_source_snapshot(
+    Path((id, snapshot_id)): Path<(i32, i32)>,
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<Json<SubscribeSourceSnapshotDto>, AppError> {
+    let db = &state.conn;
+    find_one_subscribe_source_in_db(db, id, &current_user).await?;
+    let ssm = find_one_subscribe_source_snapshot_in_db(db, id, snapshot_id).await?;
+    Ok(Json(SubscribeSourceSnapshotDto::from_orm(ssm, true)))
+}
+
 pub async fn delete_one_subscribe_source(
     State(state): State<Arc<AppState>>,
     Extension(current_user): Extension<CurrentUser>,
@@ -598,7 +1828,7 @@ pub async fn sync_one_subscribe_source(
 
     if let Some((sm, cm)) = pm.pop() {
         let cm = &cm[0];
-        sync_one_subscribe_source_with_url(sm, &cm.user_agent, db).await?;
+        sync_one_subscribe_source_with_url(sm, &cm.user_agent, &state).await?;
         Ok(())
     } else {
         Err(AppError::DbNotFound(format!(
@@ -607,3 +1837,167 @@ pub async fn sync_one_subscribe_source(
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, QueryTrait, Transaction, Value};
+    use std::collections::BTreeMap;
+
+    fn snapshot(id: i32, content: &str) -> subscribe_source_snapshot::Model {
+        subscribe_source_snapshot::Model {
+            id,
+            subscribe_source_id: 1,
+            content: content.to_string(),
+            status: 200,
+            sub_upload: None,
+            sub_download: None,
+            sub_total: None,
+            sub_expire: None,
+            fetched_at: Utc::now().naive_utc(),
+            content_hash: Some(content_hash(content)),
+        }
+    }
+
+    fn new_snapshot(content: &str) -> subscribe_source_snapshot::ActiveModel {
+        subscribe_source_snapshot::ActiveModel {
+            subscribe_source_id: Set(1),
+            content_hash: Set(Some(content_hash(content))),
+            content: Set(content.to_string()),
+            status: Set(200),
+            ..Default::default()
+        }
+    }
+
+    fn subscribe_source(content: &str, pinned_snapshot_id: Option<i32>) -> subscribe_source::Model {
+        let now = Utc::now().naive_utc();
+        subscribe_source::Model {
+            id: 1,
+            url: String::from("https://example.com/sub"),
+            created_at: now,
+            updated_at: now,
+            confluence_id: 1,
+            name: String::from("test"),
+            content: content.to_string(),
+            sub_upload: None,
+            sub_download: None,
+            sub_total: None,
+            sub_expire: None,
+            passive_sync: None,
+            proxy_server: None,
+            proxy_auth: None,
+            import_proxy_groups: None,
+            proxy_include: None,
+            proxy_exclude: None,
+            proxy_rename: None,
+            tags: vec![],
+            pinned_snapshot_id,
+            last_sync_at: None,
+            last_sync_status: None,
+            last_error: None,
+            consecutive_failures: 0,
+            fetch_overrides: None,
+            etag: None,
+            last_modified: None,
+            request_headers: None,
+        }
+    }
+
+    fn row(column: &str, value: impl Into<Value>) -> BTreeMap<&str, Value> {
+        BTreeMap::from([(column, value.into())])
+    }
+
+    #[tokio::test]
+    async fn test_record_subscribe_source_snapshot_unchanged() -> anyhow::Result<()> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![row("content_hash", Some(content_hash("a")))]])
+            .into_connection();
+
+        record_subscribe_source_snapshot(&db, 1, None, new_snapshot("a")).await?;
+
+        assert_eq!(db.into_transaction_log().len(), 1usize);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_record_subscribe_source_snapshot_prune() -> anyhow::Result<()> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![row("content_hash", Some(content_hash("a")))]])
+            .append_query_results([vec![snapshot(13, "b")]])
+            // ids past the history limit, the second one is pinned
+            .append_query_results([vec![row("id", 3), row("id", 2), row("id", 1)]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 2,
+            }])
+            .into_connection();
+
+        record_subscribe_source_snapshot(&db, 1, Some(2), new_snapshot("b")).await?;
+
+        let log = db.into_transaction_log();
+        assert_eq!(log.len(), 4usize);
+        let delete = subscribe_source_snapshot::Entity::delete_many()
+            .filter(subscribe_source_snapshot::Column::Id.is_in([3, 1]))
+            .build(DatabaseBackend::Postgres);
+        assert_eq!(log[3], Transaction::one(delete));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_subscribe_source_mux_content() -> anyhow::Result<()> {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![snapshot(2, "pinned")]])
+            .append_query_results([vec![snapshot(3, "latest")]])
+            .append_query_results([vec![snapshot(3, "latest")]])
+            .into_connection();
+
+        assert_eq!(
+            find_subscribe_source_mux_content(&db, &subscribe_source("latest", Some(2))).await?,
+            (String::from("pinned"), Some(2))
+        );
+        assert_eq!(
+            find_subscribe_source_mux_content(&db, &subscribe_source("latest", None)).await?,
+            (String::from("latest"), Some(3))
+        );
+        // edited by hand after the latest fetch
+        assert_eq!(
+            find_subscribe_source_mux_content(&db, &subscribe_source("edited", None)).await?,
+            (String::from("edited"), None)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reseal_subscribe_source_secrets() -> anyhow::Result<()> {
+        use crate::crypto::SecretKey;
+        use base64::Engine;
+        use base64::engine::general_purpose::STANDARD;
+
+        let key = |id: &str, byte: u8| {
+            SecretKey::parse(&format!("{}:{}", id, STANDARD.encode([byte; 32]))).unwrap()
+        };
+        let old = SecretCipher::new(vec![key("k1", 1)]);
+        let cipher = SecretCipher::new(vec![key("k2", 2), key("k1", 1)]);
+        let mut sm = subscribe_source("", None);
+        sm.url = cipher.seal(&sm.url)?;
+        sm.request_headers = Some(RequestHeaders(
+            [
+                (String::from("Authorization"), old.seal("Bearer sealed")?),
+                (String::from("X-Token"), String::from("plain")),
+            ]
+            .into(),
+        ));
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // the batch, then the row returned by the update
+            .append_query_results([vec![sm.clone()], vec![sm]])
+            .into_connection();
+
+        assert_eq!(reseal_subscribe_source_secrets(&db, &cipher).await?, 1);
+
+        let log = format!("{:?}", db.into_transaction_log());
+        assert!(!log.contains("\"plain\""));
+        assert!(!log.contains("enc:v1:k1:"));
+        assert_eq!(log.matches("enc:v1:k2:").count(), 3);
+        Ok(())
+    }
+}
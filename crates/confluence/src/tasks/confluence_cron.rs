@@ -1,16 +1,16 @@
 use crate::{
+    dto::SubscribeSourceSyncStatus,
     error::AppError,
     models::confluence,
     services::{
-        find_certain_confluence_profiles_and_subscribe_sources, mux_one_confluence_impl,
-        passive_sync_one_subscribe_source_with_url, AppState,
+        AppState, find_certain_confluence_profiles_and_subscribe_sources, mux_one_confluence_impl,
+        sync_confluence_subscribe_sources,
     },
 };
 use chrono::Utc;
 use chrono_tz::Tz;
 use cron::Schedule;
-use futures::future;
-use sea_orm::{prelude::*, Set, Unchanged};
+use sea_orm::{Set, Unchanged, prelude::*};
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -22,15 +22,24 @@ pub struct ConfluenceCronTask {
 impl ConfluenceCronTask {
     async fn run_one_confluence_cron(&self, cm: confluence::Model) -> Result<(), AppError> {
         let db = &self.state.conn;
-        let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, cm.id).await?;
+        let (_, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, cm.id).await?;
 
         let ua = cm.user_agent_or_default();
 
-        let sms = future::try_join_all(
-            sms.into_iter()
-                .map(|sm| passive_sync_one_subscribe_source_with_url(sm, ua, db)),
-        )
-        .await?;
+        // failed sources keep their previous content, the mux goes on with the rest
+        let sync_results = sync_confluence_subscribe_sources(sms, ua, &self.state).await;
+
+        // nothing to re-mux if every source answered not modified, a passive source may
+        // still have been synced on its own since the previous mux
+        if !sync_results.is_empty()
+            && sync_results
+                .iter()
+                .all(|r| r.status == SubscribeSourceSyncStatus::Unchanged)
+        {
+            return Ok(());
+        }
+
+        let (pms, sms) = find_certain_confluence_profiles_and_subscribe_sources(db, cm.id).await?;
 
         mux_one_confluence_impl(db, cm, sms, pms).await?;
         Ok(())